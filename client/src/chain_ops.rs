@@ -1,21 +1,79 @@
 use anyhow::Result;
-use subxt::{dynamic::Value, ext::scale_value::Composite, OnlineClient, PolkadotConfig};
+use rsa_circuit::{
+    challenge::WellFormedness,
+    utils::{Account, Domain, Setup},
+};
+use subxt::{
+    config::{substrate::BlakeTwo256, Hasher},
+    dynamic::Value,
+    ext::scale_value::Composite,
+    utils::{AccountId32, H256},
+    OnlineClient, PolkadotConfig,
+};
 use url::Url;
 
-use crate::{read_setup, signer::signer_from_phrase};
+use crate::{
+    command::{with_challenge_circuit, ChallengeKind},
+    read_challenge_setup,
+    signer::signer_from_phrase,
+};
 
 const PALLET_NAME: &str = "VkStorage";
 const EXTRINSIC_NAME: &str = "store_key";
+const STORAGE_NAME: &str = "VerificationKeys";
 
-pub async fn run_vk_registration(url: Url, phrase: String) -> Result<()> {
+/// Compute the identifier under which the verification key from `setup` is stored on chain.
+pub fn vk_hash(setup: &Setup) -> H256 {
+    BlakeTwo256::hash(&setup.serialize_vk())
+}
+
+/// Compute the domain of the contract at `address`, i.e. the deployment that proofs submitted to it have to be bound
+/// to: its address and the genesis hash of the chain.
+pub async fn fetch_domain(address: &AccountId32, url: Url) -> Result<Domain> {
+    let api = OnlineClient::<PolkadotConfig>::from_url(url).await?;
+    Ok(Domain {
+        contract: Account::new(address.0),
+        chain_id: api.genesis_hash().0,
+    })
+}
+
+/// Check whether the verification key identified by `vk_hash` is already stored in the `VkStorage` pallet.
+pub async fn is_vk_registered(api: &OnlineClient<PolkadotConfig>, vk_hash: H256) -> Result<bool> {
+    let address =
+        subxt::dynamic::storage(PALLET_NAME, STORAGE_NAME, vec![Value::from_bytes(vk_hash)]);
+    let stored = api.storage().at_latest().await?.fetch(&address).await?;
+    Ok(stored.is_some())
+}
+
+/// Read the setup of the well-formedness circuit or, if not requested, of the circuit for `challenge`.
+pub fn read_vk_setup(well_formedness: bool, challenge: ChallengeKind) -> Result<Setup> {
+    if well_formedness {
+        read_challenge_setup::<WellFormedness>()
+    } else {
+        with_challenge_circuit!(challenge, C => read_challenge_setup::<C>())
+    }
+}
+
+pub async fn run_vk_registration(
+    well_formedness: bool,
+    challenge: ChallengeKind,
+    url: Url,
+    phrase: String,
+) -> Result<()> {
     println!("⏳ Preparing for verification key registration...");
-    let vk_bytes = read_setup()?.serialize_vk();
+    let setup = read_vk_setup(well_formedness, challenge)?;
+    let vk_hash = vk_hash(&setup);
 
     let api = OnlineClient::<PolkadotConfig>::from_url(url).await?;
+    if is_vk_registered(&api, vk_hash).await? {
+        println!("✅ Verification key {vk_hash:?} is already registered, skipping");
+        return Ok(());
+    }
+
     let call = subxt::dynamic::tx(
         PALLET_NAME,
         EXTRINSIC_NAME,
-        Composite::unnamed([Value::from_bytes(&vk_bytes)]),
+        Composite::unnamed([Value::from_bytes(setup.serialize_vk())]),
     );
     println!("⏳ Preparing Signer...");
     let signer = signer_from_phrase(phrase)?;
@@ -26,6 +84,23 @@ pub async fn run_vk_registration(url: Url, phrase: String) -> Result<()> {
         .await?
         .wait_for_finalized_success()
         .await?;
-    println!("✅ Registered verification key");
+    println!("✅ Registered verification key {vk_hash:?}");
+    Ok(())
+}
+
+pub async fn run_vk_status(
+    well_formedness: bool,
+    challenge: ChallengeKind,
+    url: Url,
+) -> Result<()> {
+    let vk_hash = vk_hash(&read_vk_setup(well_formedness, challenge)?);
+    println!("🔑 Local verification key hash: \x1b[1m{vk_hash:?}\x1b[0m");
+
+    let api = OnlineClient::<PolkadotConfig>::from_url(url).await?;
+    if is_vk_registered(&api, vk_hash).await? {
+        println!("✅ Verification key is registered on chain");
+    } else {
+        println!("❌ Verification key is not registered on chain");
+    }
     Ok(())
 }
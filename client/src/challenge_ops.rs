@@ -0,0 +1,224 @@
+use std::{
+    env,
+    fs::{read_to_string, write},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::{rngs::OsRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    command::ChallengeKind, contract_ops::run_contract_deployment, factorization::is_probable_prime,
+};
+
+const CHALLENGE_SECRET_FILE: &str = "challenge-secret.json";
+/// Environment variable with the password. It is never taken from the command line, where it would end up in the shell
+/// history and in the process list.
+const PASSWORD_VARIABLE: &str = "CHALLENGE_PASSWORD";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// Number of random Miller-Rabin witnesses used when generating primes.
+const PRIMALITY_ROUNDS: usize = 40;
+
+/// Challenge together with its factors encrypted with the organizer's password.
+#[derive(Serialize, Deserialize)]
+struct ChallengeSecret {
+    /// The number to factorize.
+    n: u128,
+    /// Salt for the key derivation.
+    salt: [u8; SALT_LEN],
+    /// Encryption nonce.
+    nonce: [u8; NONCE_LEN],
+    /// Encrypted factors, authenticated together with `n`.
+    sealed_factors: Vec<u8>,
+}
+
+pub struct Deployment {
+    pub reward: u128,
+    pub url: Url,
+    pub phrase: String,
+}
+
+pub async fn run_new_challenge(
+    bits: u32,
+    force: bool,
+    deployment: Option<Deployment>,
+) -> Result<()> {
+    ensure!(
+        (10..=128).contains(&bits),
+        "Challenge size must be between 10 and 128 bits"
+    );
+    if Path::new(CHALLENGE_SECRET_FILE).exists() && !force {
+        bail!("`{CHALLENGE_SECRET_FILE}` already exists, use `--force` to overwrite it");
+    }
+    let password = read_password()?;
+
+    println!("⏳ Generating {bits}-bit challenge...");
+    let (p, q) = random_semiprime(bits, &mut OsRng);
+    let n = p * q;
+    println!("✅ Generated challenge: \x1b[1m{n}\x1b[0m");
+
+    let secret = seal(n, p, q, &password)?;
+    write(
+        CHALLENGE_SECRET_FILE,
+        serde_json::to_string_pretty(&secret)?,
+    )
+    .context("Failed to save challenge secret")?;
+    println!("💾 Saved encrypted factors to `{CHALLENGE_SECRET_FILE}`");
+
+    if let Some(Deployment {
+        reward,
+        url,
+        phrase,
+    }) = deployment
+    {
+        run_contract_deployment(n, ChallengeKind::Factoring, None, reward, None, url, phrase)
+            .await?;
+    }
+    Ok(())
+}
+
+pub fn run_reveal_challenge() -> Result<()> {
+    let password = read_password()?;
+    let secret: ChallengeSecret = serde_json::from_str(
+        &read_to_string(CHALLENGE_SECRET_FILE).context("Failed to read challenge secret")?,
+    )?;
+    let (p, q) = unseal(&secret, &password)?;
+    println!("🔓 \x1b[1m{} = {p} * {q}\x1b[0m", secret.n);
+    Ok(())
+}
+
+fn read_password() -> Result<String> {
+    let password = env::var(PASSWORD_VARIABLE)
+        .with_context(|| format!("Set the password in the `{PASSWORD_VARIABLE}` variable"))?;
+    ensure!(!password.is_empty(), "Password must not be empty");
+    Ok(password)
+}
+
+/// Generate two distinct random primes whose product has exactly `bits` bits.
+fn random_semiprime(bits: u32, rng: &mut impl RngCore) -> (u128, u128) {
+    let p_bits = bits / 2;
+    let q_bits = bits - p_bits;
+    loop {
+        let (p, q) = (random_prime(p_bits, rng), random_prime(q_bits, rng));
+        if p != q {
+            return (p, q);
+        }
+    }
+}
+
+/// Generate a random prime of exactly `bits` bits, with the two most significant bits set (so that a product of two
+/// such primes has exactly the sum of their bit lengths).
+fn random_prime(bits: u32, rng: &mut impl RngCore) -> u128 {
+    debug_assert!((5..=64).contains(&bits));
+    let top_bits = 0b11 << (bits - 2);
+    let mask = u128::MAX >> (128 - bits);
+    loop {
+        let candidate = (rng.gen::<u128>() & mask) | top_bits | 1;
+        let witnesses = (0..PRIMALITY_ROUNDS)
+            .map(|_| rng.gen_range(2..candidate - 1))
+            .collect::<Vec<_>>();
+        if is_probable_prime(candidate, &witnesses) {
+            return candidate;
+        }
+    }
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|err| anyhow!("Failed to derive encryption key: {err}"))?;
+    Ok(key)
+}
+
+fn seal(n: u128, p: u128, q: u128, password: &str) -> Result<ChallengeSecret> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(password, &salt)?);
+    let sealed_factors = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: format!("{p} {q}").as_bytes(),
+                aad: &n.to_le_bytes(),
+            },
+        )
+        .map_err(|err| anyhow!("Failed to encrypt factors: {err}"))?;
+
+    Ok(ChallengeSecret {
+        n,
+        salt,
+        nonce,
+        sealed_factors,
+    })
+}
+
+fn unseal(secret: &ChallengeSecret, password: &str) -> Result<(u128, u128)> {
+    let cipher = ChaCha20Poly1305::new(&derive_key(password, &secret.salt)?);
+    let factors = cipher
+        .decrypt(
+            Nonce::from_slice(&secret.nonce),
+            Payload {
+                msg: &secret.sealed_factors,
+                aad: &secret.n.to_le_bytes(),
+            },
+        )
+        .map_err(|_| anyhow!("Failed to decrypt factors, is the password correct?"))?;
+
+    let factors = String::from_utf8(factors)?;
+    let Some((p, q)) = factors.split_once(' ') else {
+        bail!("Malformed challenge secret");
+    };
+    let (p, q) = (p.parse::<u128>()?, q.parse::<u128>()?);
+    ensure!(
+        p.checked_mul(q) == Some(secret.n),
+        "Decrypted factors do not multiply to the challenge"
+    );
+    Ok((p, q))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::factorization::is_prime;
+
+    #[test]
+    fn semiprimes_have_requested_size() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for bits in [10, 11, 32, 63, 64, 100, 127, 128] {
+            let (p, q) = random_semiprime(bits, &mut rng);
+            assert!(is_prime(p) && is_prime(q) && p != q);
+            assert_eq!(128 - (p * q).leading_zeros(), bits);
+        }
+    }
+
+    #[test]
+    fn sealed_factors_can_be_recovered_only_with_password() {
+        let secret = seal(41 * 43, 41, 43, "password").unwrap();
+
+        assert_eq!(unseal(&secret, "password").unwrap(), (41, 43));
+        assert!(unseal(&secret, "wrong password").is_err());
+    }
+
+    #[test]
+    fn sealed_factors_are_bound_to_challenge() {
+        let mut secret = seal(41 * 43, 41, 43, "password").unwrap();
+        secret.n = 37 * 43;
+
+        assert!(unseal(&secret, "password").is_err());
+    }
+}
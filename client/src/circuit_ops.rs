@@ -1,32 +1,249 @@
-use std::fs::write;
+use std::{
+    fs::write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use anyhow::{Context, Result};
-use rsa_circuit::utils::{generate_proof, generate_setup};
+use anyhow::{bail, ensure, Context, Result};
+use rsa_circuit::{
+    challenge::{ChallengeCircuit, Factoring, WellFormedness},
+    preimage::{hash_preimage, is_canonical, FieldElementBytes},
+    utils::{
+        generate_proof, generate_setup, verify_proof, Account, Domain, MultiOpenScheme, Setup,
+    },
+    CIRCUIT_MAX_K,
+};
+use subxt::utils::AccountId32;
+use url::Url;
 
-use crate::{read_setup, signer::signer_from_phrase, CIRCUIT_MAX_K, PROOF_FILE, SNARK_SETUP_FILE};
+use crate::{
+    chain_ops::fetch_domain,
+    command::{with_challenge_circuit, ChallengeKind},
+    factorization::factorize,
+    read_challenge_setup, setup_file,
+    signer::signer_from_phrase,
+    PROOF_FILE,
+};
 
-pub fn run_snark_setup() -> Result<()> {
+pub fn run_snark_setup(
+    well_formedness: bool,
+    challenge: ChallengeKind,
+    scheme: MultiOpenScheme,
+) -> Result<()> {
     println!("⏳ Generating SNARK setup...");
-    let setup = generate_setup(CIRCUIT_MAX_K);
+    let (setup, file) = if well_formedness {
+        challenge_setup::<WellFormedness>(scheme)
+    } else {
+        with_challenge_circuit!(challenge, C => challenge_setup::<C>(scheme))
+    };
     println!("✅ Generated SNARK setup");
 
-    write(SNARK_SETUP_FILE, setup.to_bytes()).context("Failed to save SNARK setup")?;
-    println!("💾 Saved SNARK setup to `{SNARK_SETUP_FILE}`");
+    write(&file, setup.to_bytes()).context("Failed to save SNARK setup")?;
+    println!("💾 Saved SNARK setup to `{file}`");
     Ok(())
 }
 
-pub fn run_proof_generation(p: u128, q: u128, phrase: String) -> Result<()> {
+/// Generate the setup for the puzzle `C`, together with the file to save it to.
+fn challenge_setup<C: ChallengeCircuit>(scheme: MultiOpenScheme) -> (Setup, String) {
+    (C::generate_setup(scheme), setup_file::<C>())
+}
+
+/// Generate a proof that `witness` solves `statement`, bound to `account` or, if not given, to the signer's account.
+pub async fn run_proof_generation<C: ChallengeCircuit>(
+    statement: C::Statement,
+    witness: C::Witness,
+    account: Option<Account>,
+    address: AccountId32,
+    url: Url,
+    phrase: String,
+) -> Result<()> {
     println!("⏳ Preparing for SNARK proof generation...");
-    let setup = read_setup()?;
+    let setup = read_challenge_setup::<C>()?;
 
     let signer = signer_from_phrase(phrase)?;
+    let domain = fetch_domain(&address, url).await?;
 
-    let account = signer.public_key().to_account_id().0;
+    let account = account.unwrap_or_else(|| Account::new(signer.public_key().to_account_id().0));
     println!("⏳ Generating SNARK proof...");
-    let proof = generate_proof(&setup, p, q, account);
+    let Some(proof) = C::generate_proof(&setup, &statement, &witness, account, domain) else {
+        bail!("The given advices do not solve the challenge");
+    };
     println!("✅ Generated SNARK proof");
 
     write(PROOF_FILE, proof).context("Failed to save SNARK proof")?;
     println!("💾 Saved SNARK proof to `{PROOF_FILE}`");
     Ok(())
 }
+
+/// Contract (and the node to reach it) that a proof generated by `factor` is bound to.
+pub struct ProofTarget {
+    pub address: AccountId32,
+    pub url: Url,
+}
+
+pub async fn run_factorization(
+    n: u128,
+    output: Option<PathBuf>,
+    proof_target: Option<ProofTarget>,
+    phrase: String,
+) -> Result<()> {
+    println!("⏳ Factorizing {n}...");
+    let factors = factorize(n);
+    let formatted = factors
+        .iter()
+        .map(u128::to_string)
+        .collect::<Vec<_>>()
+        .join(" * ");
+    println!("✅ \x1b[1m{n} = {formatted}\x1b[0m");
+
+    let [p, q] = factors[..] else {
+        if output.is_some() || proof_target.is_some() {
+            bail!("{n} is not a product of two primes");
+        }
+        return Ok(());
+    };
+
+    if let Some(output) = output {
+        write(&output, format!("{p} {q}\n")).context("Failed to save factors")?;
+        println!("💾 Saved factors to `{}`", output.display());
+    }
+    if let Some(ProofTarget { address, url }) = proof_target {
+        run_proof_generation::<Factoring>(n, (p, q), None, address, url, phrase).await?;
+    }
+    Ok(())
+}
+
+pub fn run_report(ks: Vec<u32>, runs: u32) -> Result<()> {
+    ensure!(runs > 0, "Number of runs must be positive");
+    if let Some(k) = ks.iter().find(|k| !(CIRCUIT_MAX_K..32).contains(*k)) {
+        bail!("Cannot use k = {k}, it must be between {CIRCUIT_MAX_K} and 31");
+    }
+
+    let (p, q, account, domain) = (41, 43, Account::default(), Domain::default());
+    for k in ks {
+        println!("⏳ Measuring k = {k} ({runs} runs)...");
+        let (setup, setup_time) = measure(runs, || generate_setup(k, MultiOpenScheme::Gwc));
+        let (serialized, serialization_time) = measure(runs, || setup.clone().to_bytes());
+        let (deserialized, deserialization_time) =
+            measure(runs, || Setup::from_bytes(&mut serialized.as_slice()));
+        deserialized.context("Failed to read serialized setup")?;
+        println!(
+            "📊 k = {k}: setup {setup_time:?}, serialized setup {} bytes (write {serialization_time:?}, read {deserialization_time:?})",
+            serialized.len()
+        );
+
+        for scheme in [MultiOpenScheme::Gwc, MultiOpenScheme::Shplonk] {
+            let setup = Setup {
+                scheme,
+                ..setup.clone()
+            };
+            let (proof, proving_time) =
+                measure(runs, || generate_proof(&setup, p, q, account, domain));
+            let (valid, verification_time) = measure(runs, || {
+                verify_proof(&setup, &proof, p * q, account, domain)
+            });
+            ensure!(valid, "Generated proof is invalid");
+            println!(
+                "📊 k = {k}, {scheme:?}: proof {} bytes, proving {proving_time:?}, verification {verification_time:?}",
+                proof.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn run_circuit_info(challenge: ChallengeKind, layout: Option<PathBuf>) -> Result<()> {
+    with_challenge_circuit!(challenge, C => report_circuit::<C>(layout))
+}
+
+fn report_circuit<C: ChallengeCircuit>(layout: Option<PathBuf>) -> Result<()> {
+    println!("⏳ Measuring the `{}` circuit...", C::NAME);
+    let info = C::info();
+    println!(
+        "📊 k = {}: {} of {} usable rows, smallest k = {}",
+        info.k, info.rows, info.usable_rows, info.min_k
+    );
+    println!(
+        "📊 Columns: {} advice, {} fixed, {} instance; max degree {}",
+        info.advice_columns, info.fixed_columns, info.instance_columns, info.max_degree
+    );
+    println!(
+        "📊 Instances: {}, proof {} bytes (GWC)",
+        info.instances, info.proof_size
+    );
+
+    if let Some(layout) = layout {
+        render_layout::<C>(&layout)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "dev-graph")]
+fn render_layout<C: ChallengeCircuit>(path: &Path) -> Result<()> {
+    println!("⏳ Rendering circuit layout...");
+    C::render_layout(path).context("Failed to render circuit layout")?;
+    println!("💾 Saved circuit layout to `{}`", path.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "dev-graph"))]
+fn render_layout<C: ChallengeCircuit>(_: &Path) -> Result<()> {
+    bail!("Rendering the layout requires the client built with the `dev-graph` feature")
+}
+
+/// Run `action` `runs` times and return the last result together with the average duration.
+fn measure<T>(runs: u32, mut action: impl FnMut() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let mut result = action();
+    for _ in 1..runs {
+        result = action();
+    }
+    (result, start.elapsed() / runs)
+}
+
+pub fn run_preimage_hash(preimage: FieldElementBytes) -> Result<()> {
+    let hash = hash_preimage(&preimage).context("Preimage is not a field element")?;
+    println!("✅ \x1b[1mDigest: {}\x1b[0m", format_field_element(&hash));
+    Ok(())
+}
+
+/// Parse a hex-encoded account identifier (with or without the `0x` prefix), e.g. a 20-byte EVM address.
+pub fn parse_account(hex: &str) -> Result<Account> {
+    let bytes = decode_hex(hex).with_context(|| format!("Account `{hex}` is not hex-encoded"))?;
+    Account::from_slice(&bytes).with_context(|| format!("Account `{hex}` is too long"))
+}
+
+/// Parse a field element written as a hex-encoded number (big-endian, with or without the `0x` prefix).
+pub fn parse_field_element(hex: &str) -> Result<FieldElementBytes> {
+    let bytes = decode_hex(hex).with_context(|| format!("`{hex}` is not hex-encoded"))?;
+    ensure!(bytes.len() <= 32, "`{hex}` is too long for a field element");
+    // Field elements are encoded in little-endian bytes.
+    let mut element = [0u8; 32];
+    for (target, byte) in element.iter_mut().zip(bytes.iter().rev()) {
+        *target = *byte;
+    }
+    ensure!(
+        is_canonical(&element),
+        "`{hex}` is not smaller than the field modulus"
+    );
+    Ok(element)
+}
+
+/// Format a field element as accepted by [`parse_field_element`].
+pub fn format_field_element(element: &FieldElementBytes) -> String {
+    let hex = element
+        .iter()
+        .rev()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("0x{hex}")
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    ensure!(hex.is_ascii() && hex.len() % 2 == 0, "Odd number of digits");
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<Vec<_>, _>>()?)
+}
@@ -1,31 +1,182 @@
+use std::path::PathBuf;
+
+use clap::builder::{PossibleValue, RangedU64ValueParser};
+use rsa_circuit::{
+    challenge::ChallengeCircuit, multi_factor::MAX_FACTOR_COUNT, utils::MultiOpenScheme,
+};
+
 #[derive(clap::Parser)]
 pub enum Command {
     // ------------ LOCAL CIRCUIT-RELATED OPERATIONS -------------------------------------------------------------------
     /// Run trusted setup phase and circuit-specific processing. Write the result (SRS, proving key, verifying key) to
     /// a file.
-    SetupSnark,
-    /// Generate a proof for the given advices and write it to a file.
+    SetupSnark {
+        /// Generate the setup for the circuit proving that a challenge is well formed instead.
+        #[clap(long)]
+        well_formedness: bool,
+        /// Puzzle to generate the setup for.
+        #[clap(long, value_enum, default_value_t, conflicts_with = "well_formedness")]
+        challenge: ChallengeKind,
+        /// Multi-open scheme of the proofs. The on-chain verifier accepts GWC proofs only.
+        #[clap(long, value_enum, default_value_t)]
+        scheme: Scheme,
+    },
+    /// Generate a proof for the given advices and write it to a file. The proof is bound to the signer's account and to
+    /// the contract at `address` (on the chain behind `url`), so it cannot be submitted to any other deployment.
     GenerateProof {
         p: u128,
         q: u128,
+        address: String,
+        /// Prove the knowledge of the private exponent `d` for the public key `(p * q, e)` instead of the factors.
+        #[clap(long, requires = "d")]
+        e: Option<u64>,
+        #[clap(long, requires = "e")]
+        d: Option<u128>,
+        /// Generate the proof in the hashed public input mode, for a challenge deployed with `--hashed`.
+        #[clap(long, conflicts_with = "e")]
+        hashed: bool,
+        /// Bind the proof to this account (hex-encoded, e.g. a 20-byte EVM address) instead of the signer's one.
+        #[clap(long)]
+        account: Option<String>,
+        #[clap(long, default_value = "ws://localhost:9944")]
+        url: url::Url,
+        #[clap(long, default_value = "//Alice")]
+        phrase: String,
+    },
+    /// Compute the digest of `preimage` (a hex-encoded field element) for a hash-preimage challenge.
+    HashPreimage {
+        preimage: String,
+    },
+    /// Generate a proof of the knowledge of `preimage` (a hex-encoded field element) for the hash-preimage challenge at
+    /// `address` and write it to a file. The proof is bound just like for `generate-proof`.
+    GeneratePreimageProof {
+        preimage: String,
+        address: String,
+        /// Bind the proof to this account (hex-encoded, e.g. a 20-byte EVM address) instead of the signer's one.
+        #[clap(long)]
+        account: Option<String>,
+        #[clap(long, default_value = "ws://localhost:9944")]
+        url: url::Url,
+        #[clap(long, default_value = "//Alice")]
+        phrase: String,
+    },
+    /// Factorize a (small) number. If it is a product of two primes, the factors can be saved to a file or used to
+    /// generate a proof right away.
+    Factor {
+        n: u128,
+        /// Save the two factors to a file (in the format accepted by `play --factors`).
+        #[clap(long)]
+        output: Option<PathBuf>,
+        /// Generate a proof for the found factors, bound to the contract at `address`.
+        #[clap(long, requires = "address")]
+        generate_proof: bool,
+        #[clap(long)]
+        address: Option<String>,
+        #[clap(long, default_value = "ws://localhost:9944")]
+        url: url::Url,
+        #[clap(long, default_value = "//Alice")]
+        phrase: String,
+    },
+    /// Measure setup, proving, verification and serialization of the circuit for the given table sizes and print the
+    /// timings together with the proof and setup sizes.
+    Report {
+        /// Logarithms of the table sizes to measure.
+        #[clap(long, default_values_t = [rsa_circuit::CIRCUIT_MAX_K])]
+        k: Vec<u32>,
+        /// Number of runs to average every measurement over.
+        #[clap(long, default_value_t = 3)]
+        runs: u32,
+    },
+    /// Print the cost of the circuit for the puzzle: the rows and columns it uses, the maximum degree of the
+    /// constraints, the number of instances, the smallest table that fits it and the size of its proofs.
+    CircuitInfo {
+        #[clap(long, value_enum, default_value_t)]
+        challenge: ChallengeKind,
+        /// Render the layout of the circuit to this file (SVG if the extension is `svg`, PNG otherwise). Requires the
+        /// client to be built with the `dev-graph` feature.
+        #[clap(long)]
+        layout: Option<PathBuf>,
+    },
+
+    /// Generate a new challenge: a product of two random primes of the given total size. The factors are saved,
+    /// encrypted with the password from the `CHALLENGE_PASSWORD` environment variable, to a file in the current
+    /// directory.
+    NewChallenge {
+        #[clap(long)]
+        bits: u32,
+        /// Overwrite the previously saved challenge secret.
+        #[clap(long)]
+        force: bool,
+        /// Deploy the contract with the new challenge right away.
+        #[clap(long, requires = "reward")]
+        deploy: bool,
+        #[clap(long, requires = "deploy")]
+        reward: Option<u128>,
+        #[clap(long, default_value = "ws://localhost:9944")]
+        url: url::Url,
         #[clap(long, default_value = "//Alice")]
         phrase: String,
     },
+    /// Decrypt and print the factors of the challenge generated with `new-challenge`, using the password from the
+    /// `CHALLENGE_PASSWORD` environment variable.
+    RevealChallenge,
 
     // ------------ CHAIN OPERATIONS -----------------------------------------------------------------------------------
     /// Register verifying key on the blockchain.
     RegisterVk {
+        /// Register the verifying key of the circuit proving that a challenge is well formed instead.
+        #[clap(long)]
+        well_formedness: bool,
+        /// Puzzle to register the verifying key for.
+        #[clap(long, value_enum, default_value_t, conflicts_with = "well_formedness")]
+        challenge: ChallengeKind,
         #[clap(long, default_value = "ws://localhost:9944")]
         url: url::Url,
         #[clap(long, default_value = "//Alice")]
         phrase: String,
     },
+    /// Inspect the verifying key registration.
+    Vk {
+        #[clap(subcommand)]
+        command: VkCommand,
+    },
 
     // ------------ CONTRACT OPERATIONS --------------------------------------------------------------------------------
     BuildContract,
     DeployContract {
         challenge: u128,
         reward: u128,
+        /// Challenge to find a private exponent for the public key `(challenge, e)` instead of the factors.
+        #[clap(long, conflicts_with = "prove_well_formed")]
+        e: Option<u64>,
+        /// Deploy the challenge in the hashed public input mode, in which the caller and the deployment are bound to
+        /// the proof through their Poseidon hash.
+        #[clap(long, conflicts_with_all = ["e", "prove_well_formed"])]
+        hashed: bool,
+        /// Challenge to find this many factors instead of two, proven with the multi-factor circuit (`<N>-factor`).
+        #[clap(
+            long,
+            value_parser = RangedU64ValueParser::<usize>::new().range(2..=MAX_FACTOR_COUNT as u64),
+            conflicts_with_all = ["e", "hashed", "prove_well_formed"]
+        )]
+        factor_count: Option<usize>,
+        /// Prove that the challenge is a product of two primes with the same bit length, so that the contract can
+        /// check it at construction time.
+        #[clap(long, requires = "factors")]
+        prove_well_formed: bool,
+        /// File with the two factors separated by whitespace.
+        #[clap(long)]
+        factors: Option<PathBuf>,
+        #[clap(long, default_value = "ws://localhost:9944")]
+        url: url::Url,
+        #[clap(long, default_value = "//Alice")]
+        phrase: String,
+    },
+    /// Deploy the contract with a challenge to find a preimage of `hash` (a hex-encoded field element, e.g. printed by
+    /// `hash-preimage`).
+    DeployPreimageContract {
+        hash: String,
+        reward: u128,
         #[clap(long, default_value = "ws://localhost:9944")]
         url: url::Url,
         #[clap(long, default_value = "//Alice")]
@@ -38,6 +189,182 @@ pub enum Command {
         #[clap(long, default_value = "//Alice")]
         phrase: String,
     },
+    /// Solve the challenge in one go: read the number to factorize from the contract, generate a proof bound to the
+    /// signer's account (in the public input mode the challenge was deployed with), verify it locally, dry-run and
+    /// submit it. Progress is saved after every stage, so an
+    /// interrupted run can be resumed by repeating the command.
+    Play {
+        address: String,
+        #[clap(requires = "q", conflicts_with = "factors")]
+        p: Option<u128>,
+        q: Option<u128>,
+        /// File with the two factors separated by whitespace.
+        #[clap(long)]
+        factors: Option<PathBuf>,
+        /// Discard the progress saved by an interrupted run.
+        #[clap(long)]
+        restart: bool,
+        #[clap(long, default_value = "ws://localhost:9944")]
+        url: url::Url,
+        #[clap(long, default_value = "//Alice")]
+        phrase: String,
+    },
+    /// Follow the finalized blocks and report every solution attempt, win and termination of the contract.
+    Watch {
+        address: String,
+        /// Print every event as a single JSON line instead of a human-readable message.
+        #[clap(long)]
+        json: bool,
+        #[clap(long, default_value = "ws://localhost:9944")]
+        url: url::Url,
+    },
+}
+
+/// Puzzle that a challenge is built around (see `rsa_circuit::challenge`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChallengeKind {
+    /// Find the factors of the challenge.
+    #[default]
+    Factoring,
+    /// Find the factors of the challenge, with the context bound through its hash.
+    HashedFactoring,
+    /// Find a private exponent for the RSA public key.
+    PrivateKey,
+    /// Find a preimage of a Poseidon digest.
+    HashPreimage,
+    /// Find the given number of factors of the challenge (between 2 and `MAX_FACTOR_COUNT`).
+    MultiFactor(usize),
+}
+
+/// Every puzzle that can be selected, with the multi-factor one for each supported number of factors.
+const CHALLENGE_KINDS: [ChallengeKind; 11] = [
+    ChallengeKind::Factoring,
+    ChallengeKind::HashedFactoring,
+    ChallengeKind::PrivateKey,
+    ChallengeKind::HashPreimage,
+    ChallengeKind::MultiFactor(2),
+    ChallengeKind::MultiFactor(3),
+    ChallengeKind::MultiFactor(4),
+    ChallengeKind::MultiFactor(5),
+    ChallengeKind::MultiFactor(6),
+    ChallengeKind::MultiFactor(7),
+    ChallengeKind::MultiFactor(8),
+];
+
+/// Evaluate `$body` with `$circuit` standing for the `ChallengeCircuit` of the puzzle `$challenge` (the trait itself has
+/// to be in scope to reach its items).
+macro_rules! with_challenge_circuit {
+    ($challenge:expr, $circuit:ident => $body:expr) => {{
+        use rsa_circuit::challenge::{
+            Factoring, HashPreimage, HashedFactoring, MultiFactor, PrivateKey,
+        };
+        use $crate::command::ChallengeKind;
+
+        match $challenge {
+            ChallengeKind::Factoring => {
+                type $circuit = Factoring;
+                $body
+            }
+            ChallengeKind::HashedFactoring => {
+                type $circuit = HashedFactoring;
+                $body
+            }
+            ChallengeKind::PrivateKey => {
+                type $circuit = PrivateKey;
+                $body
+            }
+            ChallengeKind::HashPreimage => {
+                type $circuit = HashPreimage;
+                $body
+            }
+            ChallengeKind::MultiFactor(2) => {
+                type $circuit = MultiFactor<2>;
+                $body
+            }
+            ChallengeKind::MultiFactor(3) => {
+                type $circuit = MultiFactor<3>;
+                $body
+            }
+            ChallengeKind::MultiFactor(4) => {
+                type $circuit = MultiFactor<4>;
+                $body
+            }
+            ChallengeKind::MultiFactor(5) => {
+                type $circuit = MultiFactor<5>;
+                $body
+            }
+            ChallengeKind::MultiFactor(6) => {
+                type $circuit = MultiFactor<6>;
+                $body
+            }
+            ChallengeKind::MultiFactor(7) => {
+                type $circuit = MultiFactor<7>;
+                $body
+            }
+            ChallengeKind::MultiFactor(8) => {
+                type $circuit = MultiFactor<8>;
+                $body
+            }
+            ChallengeKind::MultiFactor(factors) => {
+                unreachable!("There is no circuit for {factors} factors")
+            }
+        }
+    }};
+}
+pub(crate) use with_challenge_circuit;
+
+/// Selects the puzzle by the name of its circuit, e.g. `private-key` or `3-factor`.
+impl clap::ValueEnum for ChallengeKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &CHALLENGE_KINDS
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let help = match self {
+            ChallengeKind::Factoring => "Find the factors of the challenge",
+            ChallengeKind::HashedFactoring => {
+                "Find the factors of the challenge, with the context bound through its hash"
+            }
+            ChallengeKind::PrivateKey => "Find a private exponent for the RSA public key",
+            ChallengeKind::HashPreimage => "Find a preimage of a Poseidon digest",
+            ChallengeKind::MultiFactor(_) => "Find this many factors of the challenge",
+        };
+        Some(PossibleValue::new(with_challenge_circuit!(*self, C => C::NAME)).help(help))
+    }
+}
+
+/// Multi-open scheme of the KZG commitment (see `rsa_circuit::utils::MultiOpenScheme`).
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum Scheme {
+    /// Gabizon-Williamson-Ciobotaru scheme, expected by the on-chain verifier.
+    #[default]
+    Gwc,
+    /// SHPLONK, for verification off chain.
+    Shplonk,
+}
+
+impl From<Scheme> for MultiOpenScheme {
+    fn from(scheme: Scheme) -> Self {
+        match scheme {
+            Scheme::Gwc => MultiOpenScheme::Gwc,
+            Scheme::Shplonk => MultiOpenScheme::Shplonk,
+        }
+    }
+}
+
+#[derive(clap::Subcommand)]
+pub enum VkCommand {
+    /// Print the hash of the local verifying key and check whether it is registered on the blockchain.
+    Status {
+        /// Check the verifying key of the circuit proving that a challenge is well formed instead.
+        #[clap(long)]
+        well_formedness: bool,
+        /// Puzzle to check the verifying key for.
+        #[clap(long, value_enum, default_value_t, conflicts_with = "well_formedness")]
+        challenge: ChallengeKind,
+        #[clap(long, default_value = "ws://localhost:9944")]
+        url: url::Url,
+    },
 }
 
 #[cfg(test)]
@@ -49,4 +376,14 @@ mod tests {
         use clap::CommandFactory;
         Command::command().debug_assert()
     }
+
+    #[test]
+    fn selects_challenge_by_circuit_name() {
+        use clap::ValueEnum;
+        let parse = |name| ChallengeKind::from_str(name, false);
+        assert_eq!(parse("private-key"), Ok(ChallengeKind::PrivateKey));
+        assert_eq!(parse("2-factor"), Ok(ChallengeKind::MultiFactor(2)));
+        assert_eq!(parse("8-factor"), Ok(ChallengeKind::MultiFactor(8)));
+        assert!(parse("9-factor").is_err());
+    }
 }
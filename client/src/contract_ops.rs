@@ -1,19 +1,26 @@
 use std::fs::read;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use contract_build::{
     BuildMode, ExecuteArgs, ManifestPath, OptimizationPasses, Verbosity, DEFAULT_MAX_MEMORY_PAGES,
 };
 use contract_extrinsics::{
-    BalanceVariant, CallCommandBuilder, ExtrinsicOptsBuilder, InstantiateCommandBuilder,
+    BalanceVariant, CallCommandBuilder, CallExec, ExtrinsicOptsBuilder, InstantiateCommandBuilder,
 };
-use subxt::{
-    config::{substrate::BlakeTwo256, Hasher},
-    utils::AccountId32,
+use rsa_circuit::{
+    challenge::{ChallengeCircuit, HashPreimage, PrivateKey, WellFormedness},
+    preimage::FieldElementBytes,
+    utils::{Account, Domain},
+    well_formedness::{Semiprime, MAX_FACTOR_BITS, MIN_FACTOR_BITS},
 };
+use subxt::{ext::codec::Decode, utils::AccountId32, OnlineClient, PolkadotConfig};
 use url::Url;
 
-use crate::{get_contract_manifest, read_setup, PROOF_FILE};
+use crate::{
+    chain_ops::{is_vk_registered, read_vk_setup, vk_hash},
+    command::ChallengeKind,
+    get_contract_manifest, read_challenge_setup, PROOF_FILE,
+};
 
 pub fn run_contract_build() -> Result<()> {
     println!("⏳ Building contract...");
@@ -28,19 +35,87 @@ pub fn run_contract_build() -> Result<()> {
     Ok(())
 }
 
+/// Deploy the contract with `challenge`, to be solved with the factoring circuit selected by `kind` (the plain, the
+/// hashed or a multi-factor one). If the factors are given, the deployment comes with a proof that the challenge is well
+/// formed, which is checked by the contract constructor. If `e` is given, the challenge is to find a private exponent
+/// for the public key `(challenge, e)` instead.
 pub async fn run_contract_deployment(
     challenge: u128,
+    kind: ChallengeKind,
+    e: Option<u64>,
+    reward: u128,
+    well_formedness_factors: Option<(u128, u128)>,
+    url: Url,
+    phrase: String,
+) -> Result<()> {
+    println!("⏳ Deploying contract...");
+
+    // Puzzles other than factoring are deployed with the generic constructor, which takes the whole statement. The
+    // multi-factor circuits share the public input, and so the constructor, of the factoring one.
+    let (setup, statement) = match e {
+        None => (read_vk_setup(false, kind)?, challenge.to_string()),
+        Some(e) => (
+            read_challenge_setup::<PrivateKey>()?,
+            format!("PrivateKey {{ n: {challenge}, e: {e} }}"),
+        ),
+    };
+    let vk_hash = vk_hash(&setup);
+    let api = OnlineClient::<PolkadotConfig>::from_url(url.clone()).await?;
+    if !is_vk_registered(&api, vk_hash).await? {
+        bail!("Verification key {vk_hash:?} is not registered on chain, run `register-vk` first");
+    }
+
+    let mut args = vec![
+        statement,
+        format!("{vk_hash:?}"),
+        format!("{:?}", api.genesis_hash()),
+    ];
+    let constructor = match (well_formedness_factors, e) {
+        (None, None) if kind == ChallengeKind::HashedFactoring => "new_hashed",
+        (None, None) => "new",
+        (None, Some(_)) => "new_with_statement",
+        (Some((p, q)), _) => {
+            args.extend(prove_well_formedness(&api, challenge, p, q).await?);
+            "new_well_formed"
+        }
+    };
+
+    instantiate(constructor, args, reward, url, phrase).await
+}
+
+/// Deploy the contract with a challenge to find a preimage of `hash`.
+pub async fn run_preimage_contract_deployment(
+    hash: FieldElementBytes,
     reward: u128,
     url: Url,
     phrase: String,
 ) -> Result<()> {
     println!("⏳ Deploying contract...");
 
-    let setup = read_setup()?;
-    let vk_hash = BlakeTwo256::hash(&setup.serialize_vk());
+    let vk_hash = vk_hash(&read_challenge_setup::<HashPreimage>()?);
+    let api = OnlineClient::<PolkadotConfig>::from_url(url.clone()).await?;
+    if !is_vk_registered(&api, vk_hash).await? {
+        bail!("Verification key {vk_hash:?} is not registered on chain, run `register-vk --challenge hash-preimage` first");
+    }
+
+    let args = vec![
+        format!("{hash:?}"),
+        format!("{vk_hash:?}"),
+        format!("{:?}", api.genesis_hash()),
+    ];
+    instantiate("new_hash_preimage", args, reward, url, phrase).await
+}
 
+async fn instantiate(
+    constructor: &str,
+    args: Vec<String>,
+    reward: u128,
+    url: Url,
+    phrase: String,
+) -> Result<()> {
     let command = InstantiateCommandBuilder::default()
-        .args(vec![challenge.to_string(), format!("{vk_hash:?}")])
+        .constructor(constructor)
+        .args(args)
         .value(BalanceVariant::Default(reward))
         .extrinsic_opts(
             ExtrinsicOptsBuilder::default()
@@ -53,7 +128,10 @@ pub async fn run_contract_deployment(
         .await?;
 
     println!("⏳ Instantiating contract...");
-    let result = command.instantiate(None).await.unwrap();
+    let result = command
+        .instantiate(None)
+        .await
+        .map_err(|err| anyhow!("Failed to instantiate the contract: {err:?}"))?;
     println!(
         "✅ Contract deployed at address: \x1b[1m{}\x1b[0m",
         result.contract_address
@@ -61,15 +139,172 @@ pub async fn run_contract_deployment(
     Ok(())
 }
 
+/// Generate a proof that `challenge = p * q` is a well-formed semiprime and return the remaining arguments of the
+/// `new_well_formed` constructor.
+async fn prove_well_formedness(
+    api: &OnlineClient<PolkadotConfig>,
+    challenge: u128,
+    p: u128,
+    q: u128,
+) -> Result<Vec<String>> {
+    ensure!(
+        p.checked_mul(q) == Some(challenge),
+        "Factors do not multiply to the challenge"
+    );
+    let factor_bits = u128::BITS - p.leading_zeros();
+    ensure!(
+        factor_bits == u128::BITS - q.leading_zeros(),
+        "Factors must have the same bit length"
+    );
+
+    let setup = read_challenge_setup::<WellFormedness>()?;
+    let vk_hash = vk_hash(&setup);
+    if !is_vk_registered(api, vk_hash).await? {
+        bail!("Verification key {vk_hash:?} is not registered on chain, run `register-vk --well-formedness`");
+    }
+
+    println!("⏳ Generating well-formedness proof...");
+    // The proof is about the challenge only, so it is not bound to any account nor deployment.
+    let semiprime = Semiprime {
+        n: challenge,
+        factor_bits,
+    };
+    let Some(proof) = WellFormedness::generate_proof(
+        &setup,
+        &semiprime,
+        &(p, q),
+        Account::default(),
+        Domain::default(),
+    ) else {
+        bail!("Factors must be primes with {MIN_FACTOR_BITS} to {MAX_FACTOR_BITS} bits");
+    };
+    println!("✅ Generated well-formedness proof");
+
+    Ok(vec![
+        factor_bits.to_string(),
+        format!("{vk_hash:?}"),
+        format!("{proof:?}"),
+    ])
+}
+
 pub async fn run_submission(address: AccountId32, url: Url, phrase: String) -> Result<()> {
     println!("⏳ Submitting solution...");
     let proof = read(PROOF_FILE).context("Failed to read SNARK proof")?;
     println!("✅ Loaded SNARK proof from `{PROOF_FILE}`");
 
+    if submit_solution(address, proof, url, phrase).await? {
+        println!("✅ \x1b[1mChallenge solved!\x1b[0m");
+    } else {
+        println!("❌ \x1b[1mChallenge not solved, proof found to be incorrect\x1b[0m");
+    }
+    Ok(())
+}
+
+/// Statement of the challenge, as returned by the `statement` message of the contract (the variants must follow the
+/// contract's `Statement`, as they are decoded by their index).
+#[derive(Clone, Copy, Debug, Decode)]
+#[codec(crate = subxt::ext::codec)]
+pub enum Statement {
+    Factoring { n: u128 },
+    HashedFactoring { n: u128 },
+    PrivateKey { n: u128, e: u64 },
+    HashPreimage { hash: FieldElementBytes },
+}
+
+/// Read the number to factorize from the contract at `address`, together with whether the proof must be generated in
+/// the hashed public input mode. Fails if the challenge is not to factorize a number (the only puzzle that `play` can
+/// solve).
+pub async fn read_challenge(
+    address: AccountId32,
+    url: Url,
+    phrase: String,
+) -> Result<(u128, bool)> {
+    match read_statement(address, url, phrase).await? {
+        Statement::Factoring { n } => Ok((n, false)),
+        Statement::HashedFactoring { n } => Ok((n, true)),
+        statement => bail!("The contract challenge is not supported: {statement:?}"),
+    }
+}
+
+/// Read the statement of the challenge from the contract at `address`.
+pub async fn read_statement(address: AccountId32, url: Url, phrase: String) -> Result<Statement> {
+    let command = contract_call(address, "statement", vec![], url, phrase).await?;
+    let result = command
+        .call_dry_run()
+        .await?
+        .result
+        .map_err(|err| anyhow!("Failed to read the challenge: {err:?}"))?;
+    if result.did_revert() {
+        bail!("Failed to read the challenge: contract reverted");
+    }
+
+    // ink! wraps every message result in `Result<_, LangError>`.
+    match <Result<Statement, u8>>::decode(&mut result.data.as_slice())? {
+        Ok(statement) => Ok(statement),
+        Err(err) => bail!("Failed to read the challenge: ink! error {err}"),
+    }
+}
+
+/// Simulate submitting `proof` to the contract at `address` without sending any transaction. Fails if the contract
+/// would not accept the proof.
+pub async fn dry_run_solution(
+    address: AccountId32,
+    proof: Vec<u8>,
+    url: Url,
+    phrase: String,
+) -> Result<()> {
+    let command = contract_call(address, "solve", vec![format!("{proof:?}")], url, phrase).await?;
+    let result = command
+        .call_dry_run()
+        .await?
+        .result
+        .map_err(|err| anyhow!("Dry run failed: {err:?}"))?;
+    if result.did_revert() {
+        bail!("Dry run failed: contract reverted");
+    }
+
+    // A rejected proof does not revert the call: `solve` emits `ChallengeStillTooHard` and returns normally. An accepted
+    // one terminates the contract instead, so the message never returns and leaves no return value behind.
+    if result.data.is_empty() {
+        return Ok(());
+    }
+    match <Result<(), u8>>::decode(&mut result.data.as_slice())? {
+        Ok(()) => bail!("Dry run failed: proof rejected by the contract (`ChallengeStillTooHard`)"),
+        Err(err) => bail!("Dry run failed: ink! error {err}"),
+    }
+}
+
+/// Submit `proof` to the contract at `address`. Returns `true` if the challenge has been solved.
+pub async fn submit_solution(
+    address: AccountId32,
+    proof: Vec<u8>,
+    url: Url,
+    phrase: String,
+) -> Result<bool> {
+    let command = contract_call(address, "solve", vec![format!("{proof:?}")], url, phrase).await?;
+
+    println!("⏳ Calling contract...");
+    let events = command
+        .call(None)
+        .await
+        .map_err(|err| anyhow!("Failed to call the contract: {err:?}"))?;
+    println!("✅ Contract called");
+
+    let event_log = events.display_events(Verbosity::Default, command.token_metadata())?;
+    Ok(event_log.contains("ChallengeSolved"))
+}
+
+async fn contract_call(
+    address: AccountId32,
+    message: &str,
+    args: Vec<String>,
+    url: Url,
+    phrase: String,
+) -> Result<CallExec> {
     let command = CallCommandBuilder::default()
         .contract(address)
-        .message("solve")
-        .args(vec![format!("{proof:?}")])
+        .message(message)
+        .args(args)
         .extrinsic_opts(
             ExtrinsicOptsBuilder::default()
                 .suri(phrase)
@@ -79,16 +314,5 @@ pub async fn run_submission(address: AccountId32, url: Url, phrase: String) -> R
         )
         .done()
         .await?;
-
-    println!("⏳ Calling contract...");
-    let events = command.call(None).await.unwrap();
-    println!("✅ Contract called");
-
-    let event_log = events.display_events(Verbosity::Default, command.token_metadata())?;
-    if event_log.contains("ChallengeSolved") {
-        println!("✅ \x1b[1mChallenge solved!\x1b[0m");
-    } else {
-        println!("❌ \x1b[1mChallenge not solved, proof found to be incorrect\x1b[0m");
-    }
-    Ok(())
+    Ok(command)
 }
@@ -0,0 +1,340 @@
+//! Integer factorization for (relatively) small challenges.
+//!
+//! Numbers are factorized with trial division by small primes followed by Pollard's rho (in Brent's variant) for the
+//! remaining cofactors. This is instant for anything up to ~80 bits and takes at most minutes for 128-bit semiprimes
+//! with balanced factors, which is more than enough for tests and demos.
+
+/// Primes used for trial division and as Miller-Rabin witnesses.
+const SMALL_PRIMES: [u128; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Trial division is continued up to this bound (exclusive) before switching to Pollard's rho.
+const TRIAL_DIVISION_BOUND: u128 = 1 << 12;
+
+/// Number of rho steps whose differences are multiplied together before computing a single gcd.
+const RHO_BATCH: usize = 128;
+
+/// Return all prime factors of `n` (with multiplicity) in ascending order. For `n < 2` the result is empty.
+pub fn factorize(mut n: u128) -> Vec<u128> {
+    let mut factors = vec![];
+    if n < 2 {
+        return factors;
+    }
+
+    let mut d = 2;
+    while d < TRIAL_DIVISION_BOUND && d * d <= n {
+        while n % d == 0 {
+            factors.push(d);
+            n /= d;
+        }
+        d += if d == 2 { 1 } else { 2 };
+    }
+
+    let mut pending = vec![n];
+    while let Some(m) = pending.pop() {
+        if m == 1 {
+            continue;
+        }
+        if is_prime(m) {
+            factors.push(m);
+            continue;
+        }
+        let divisor = pollard_rho(m);
+        pending.push(divisor);
+        pending.push(m / divisor);
+    }
+
+    factors.sort_unstable();
+    factors
+}
+
+/// Miller-Rabin primality test.
+///
+/// The test is deterministic for `n < 3.3 * 10^24` (~81 bits). Above that, the probability of a composite number
+/// passing all the rounds is smaller than `4^-25`.
+pub fn is_prime(n: u128) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+    is_probable_prime(n, &SMALL_PRIMES)
+}
+
+/// Miller-Rabin test of an odd `n > 2` with the given witnesses.
+pub fn is_probable_prime(n: u128, witnesses: &[u128]) -> bool {
+    debug_assert!(n > 2 && n % 2 == 1);
+
+    let ring = Montgomery::new(n);
+    let one = ring.one();
+    let minus_one = ring.sub(ring.zero(), one);
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    'witness: for &a in witnesses {
+        let a = a % n;
+        if a == 0 {
+            continue;
+        }
+        let mut x = ring.pow(ring.encode(a), d);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = ring.mul(x, x);
+            if x == minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Find a nontrivial divisor of an odd composite `n` with Pollard's rho algorithm (Brent's variant).
+fn pollard_rho(n: u128) -> u128 {
+    let ring = Montgomery::new(n);
+
+    // Every choice of the polynomial `x^2 + c` and the starting point might fail, so we keep trying different ones.
+    for c in 1.. {
+        let c = ring.encode(c);
+        let f = |x| ring.add(ring.mul(x, x), c);
+
+        let mut y = ring.encode(2);
+        let mut x = y;
+        let mut ys = y;
+        let mut product = ring.one();
+        let mut g = 1;
+        let mut r = 1;
+
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..RHO_BATCH.min(r - k) {
+                    y = f(y);
+                    product = ring.mul(product, ring.sub(x, y));
+                }
+                g = gcd(ring.decode(product), n);
+                k += RHO_BATCH;
+            }
+            r *= 2;
+        }
+
+        // The batch overshot, so we have to go back and check the steps one by one.
+        if g == n {
+            g = 1;
+            while g == 1 {
+                ys = f(ys);
+                g = gcd(ring.decode(ring.sub(x, ys)), n);
+            }
+        }
+
+        if g != n {
+            return g;
+        }
+    }
+    unreachable!("composite numbers always have a divisor")
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Arithmetic modulo an odd `n` in the Montgomery form (with `R = 2^128`).
+struct Montgomery {
+    n: u128,
+    /// `-n^-1 mod R`.
+    n_neg_inv: u128,
+    /// `R^2 mod n`.
+    r2: u128,
+}
+
+impl Montgomery {
+    fn new(n: u128) -> Self {
+        debug_assert!(n % 2 == 1);
+
+        // Newton iteration: every step doubles the number of correct low bits (`n` itself is correct on 3 bits).
+        let mut inv = n;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u128.wrapping_sub(n.wrapping_mul(inv)));
+        }
+
+        let r = (u128::MAX % n + 1) % n;
+        let mut r2 = r;
+        for _ in 0..128 {
+            r2 = add_mod(r2, r2, n);
+        }
+
+        Self {
+            n,
+            n_neg_inv: inv.wrapping_neg(),
+            r2,
+        }
+    }
+
+    fn zero(&self) -> u128 {
+        0
+    }
+
+    fn one(&self) -> u128 {
+        self.encode(1)
+    }
+
+    fn encode(&self, a: u128) -> u128 {
+        self.mul(a % self.n, self.r2)
+    }
+
+    fn decode(&self, a: u128) -> u128 {
+        self.reduce(0, a)
+    }
+
+    fn add(&self, a: u128, b: u128) -> u128 {
+        add_mod(a, b, self.n)
+    }
+
+    fn sub(&self, a: u128, b: u128) -> u128 {
+        if a >= b {
+            a - b
+        } else {
+            a.wrapping_sub(b).wrapping_add(self.n)
+        }
+    }
+
+    fn mul(&self, a: u128, b: u128) -> u128 {
+        let (hi, lo) = mul_wide(a, b);
+        self.reduce(hi, lo)
+    }
+
+    fn pow(&self, mut base: u128, mut exp: u128) -> u128 {
+        let mut result = self.one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = self.mul(result, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Montgomery reduction of `hi * R + lo` (which must be smaller than `n * R`).
+    fn reduce(&self, hi: u128, lo: u128) -> u128 {
+        let m = lo.wrapping_mul(self.n_neg_inv);
+        let (mn_hi, mn_lo) = mul_wide(m, self.n);
+        // `lo + mn_lo` is divisible by `R`, so we only need its carry.
+        let carry = lo.overflowing_add(mn_lo).1 as u128;
+        let (sum, overflow) = hi.overflowing_add(mn_hi);
+        let (sum, overflow2) = sum.overflowing_add(carry);
+        if overflow || overflow2 || sum >= self.n {
+            sum.wrapping_sub(self.n)
+        } else {
+            sum
+        }
+    }
+}
+
+fn add_mod(a: u128, b: u128, n: u128) -> u128 {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= n {
+        sum.wrapping_sub(n)
+    } else {
+        sum
+    }
+}
+
+/// Full 256-bit product of `a` and `b` as `(high, low)` halves.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let middle = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    let lo = (middle << 64) | (lo_lo & MASK);
+    let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (middle >> 64);
+    (hi, lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factorizes_readme_challenge() {
+        assert_eq!(factorize(1763), vec![41, 43]);
+    }
+
+    #[test]
+    fn handles_trivial_inputs() {
+        assert_eq!(factorize(0), Vec::<u128>::new());
+        assert_eq!(factorize(1), Vec::<u128>::new());
+        assert_eq!(factorize(2), vec![2]);
+        assert_eq!(factorize(97), vec![97]);
+    }
+
+    #[test]
+    fn factorizes_numbers_with_repeated_factors() {
+        assert_eq!(factorize(1 << 20), vec![2; 20]);
+        assert_eq!(
+            factorize(2 * 2 * 3 * 41 * 41 * 43),
+            vec![2, 2, 3, 41, 41, 43]
+        );
+    }
+
+    #[test]
+    fn factorizes_semiprimes() {
+        // Largest primes below 2^32.
+        assert_eq!(
+            factorize(4294967291 * 4294967279),
+            vec![4294967279, 4294967291]
+        );
+        // Largest prime below 2^40 and largest prime below 2^31.
+        assert_eq!(
+            factorize(1099511627689 * 2147483647),
+            vec![2147483647, 1099511627689]
+        );
+        // Square of the largest prime below 2^32.
+        assert_eq!(factorize(4294967291 * 4294967291), vec![4294967291; 2]);
+    }
+
+    #[test]
+    fn recognizes_primes() {
+        assert!(is_prime(2));
+        assert!(is_prime(4294967291));
+        assert!(is_prime(2305843009213693951));
+        // 2^127 - 1 is a Mersenne prime.
+        assert!(is_prime((1 << 127) - 1));
+        // Largest prime below 2^128.
+        assert!(is_prime(u128::MAX - 158));
+
+        assert!(!is_prime(1));
+        assert!(!is_prime(1763));
+        // Carmichael number.
+        assert!(!is_prime(561));
+        assert!(!is_prime(4294967291 * 4294967279));
+    }
+
+    #[test]
+    fn wide_multiplication_is_correct() {
+        assert_eq!(mul_wide(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        assert_eq!(mul_wide(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(mul_wide(41, 43), (0, 1763));
+    }
+}
@@ -6,51 +6,244 @@ use std::{
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use rsa_circuit::utils::Setup;
+use rsa_circuit::{
+    challenge::{ChallengeCircuit, Factoring, HashPreimage, HashedFactoring, PrivateKey},
+    preimage::hash_preimage,
+    private_key::PublicKey,
+    utils::Setup,
+};
 use subxt::utils::AccountId32;
 
 use crate::{
-    chain_ops::run_vk_registration,
-    circuit_ops::{run_proof_generation, run_snark_setup},
-    command::Command,
-    contract_ops::{run_contract_build, run_contract_deployment, run_submission},
+    chain_ops::{run_vk_registration, run_vk_status},
+    challenge_ops::{run_new_challenge, run_reveal_challenge, Deployment},
+    circuit_ops::{
+        parse_account, parse_field_element, run_circuit_info, run_factorization, run_preimage_hash,
+        run_proof_generation, run_report, run_snark_setup, ProofTarget,
+    },
+    command::{ChallengeKind, Command, VkCommand},
+    contract_ops::{
+        run_contract_build, run_contract_deployment, run_preimage_contract_deployment,
+        run_submission,
+    },
+    play_ops::{read_factors, run_play, Factors},
+    watch_ops::run_watch,
 };
 
-const CIRCUIT_MAX_K: u32 = 5;
 const SNARK_SETUP_FILE: &str = "snark-setup";
 const PROOF_FILE: &str = "submission-data";
 
 mod chain_ops;
+mod challenge_ops;
 mod circuit_ops;
 mod command;
 mod contract_ops;
+mod factorization;
+mod play_ops;
 mod signer;
+mod watch_ops;
+
+fn read_challenge_setup<C: ChallengeCircuit>() -> Result<Setup> {
+    read_setup_file(&setup_file::<C>())
+}
+
+/// File with the setup of the circuit for the puzzle `C`. The factoring one keeps the original name.
+fn setup_file<C: ChallengeCircuit>() -> String {
+    match C::NAME {
+        name if name == Factoring::NAME => SNARK_SETUP_FILE.to_string(),
+        name => format!("{name}-{SNARK_SETUP_FILE}"),
+    }
+}
 
-fn read_setup() -> Result<Setup> {
-    let setup_serialized = read(SNARK_SETUP_FILE).context("Failed to read SNARK setup")?;
-    let setup = Setup::from_bytes(&mut setup_serialized.as_slice());
-    println!("✅ Loaded SNARK setup from `{SNARK_SETUP_FILE}`");
+fn read_setup_file(file: &str) -> Result<Setup> {
+    let setup_serialized = read(file).context("Failed to read SNARK setup")?;
+    let setup = Setup::from_bytes(&mut setup_serialized.as_slice())
+        .with_context(|| format!("Failed to parse SNARK setup `{file}`"))?;
+    println!("✅ Loaded SNARK setup from `{file}`");
     Ok(setup)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     match Command::parse() {
-        Command::SetupSnark => run_snark_setup()?,
-        Command::GenerateProof { p, q, phrase } => run_proof_generation(p, q, phrase)?,
-        Command::RegisterVk { url, phrase } => run_vk_registration(url, phrase).await?,
+        Command::SetupSnark {
+            well_formedness,
+            challenge,
+            scheme,
+        } => run_snark_setup(well_formedness, challenge, scheme.into())?,
+        Command::GenerateProof {
+            p,
+            q,
+            address,
+            e,
+            d,
+            hashed,
+            account,
+            url,
+            phrase,
+        } => {
+            let account = account.as_deref().map(parse_account).transpose()?;
+            let address = AccountId32::from_str(&address)?;
+            let n = p
+                .checked_mul(q)
+                .context("Product of the factors does not fit into 128 bits")?;
+            match e.zip(d) {
+                None if hashed => {
+                    run_proof_generation::<HashedFactoring>(
+                        n,
+                        (p, q),
+                        account,
+                        address,
+                        url,
+                        phrase,
+                    )
+                    .await?
+                }
+                None => {
+                    run_proof_generation::<Factoring>(n, (p, q), account, address, url, phrase)
+                        .await?
+                }
+                Some((e, d)) => {
+                    let key = PublicKey { n, e };
+                    run_proof_generation::<PrivateKey>(
+                        key,
+                        (p, q, d),
+                        account,
+                        address,
+                        url,
+                        phrase,
+                    )
+                    .await?
+                }
+            }
+        }
+        Command::HashPreimage { preimage } => run_preimage_hash(parse_field_element(&preimage)?)?,
+        Command::GeneratePreimageProof {
+            preimage,
+            address,
+            account,
+            url,
+            phrase,
+        } => {
+            let preimage = parse_field_element(&preimage)?;
+            let hash = hash_preimage(&preimage).context("Preimage is not a field element")?;
+            let account = account.as_deref().map(parse_account).transpose()?;
+            let address = AccountId32::from_str(&address)?;
+            run_proof_generation::<HashPreimage>(hash, preimage, account, address, url, phrase)
+                .await?
+        }
+        Command::Factor {
+            n,
+            output,
+            generate_proof,
+            address,
+            url,
+            phrase,
+        } => {
+            let proof_target = match address.filter(|_| generate_proof) {
+                Some(address) => Some(ProofTarget {
+                    address: AccountId32::from_str(&address)?,
+                    url,
+                }),
+                None => None,
+            };
+            run_factorization(n, output, proof_target, phrase).await?
+        }
+        Command::Report { k, runs } => run_report(k, runs)?,
+        Command::CircuitInfo { challenge, layout } => run_circuit_info(challenge, layout)?,
+        Command::NewChallenge {
+            bits,
+            force,
+            deploy,
+            reward,
+            url,
+            phrase,
+        } => {
+            let deployment = reward.filter(|_| deploy).map(|reward| Deployment {
+                reward,
+                url,
+                phrase,
+            });
+            run_new_challenge(bits, force, deployment).await?
+        }
+        Command::RevealChallenge => run_reveal_challenge()?,
+        Command::RegisterVk {
+            well_formedness,
+            challenge,
+            url,
+            phrase,
+        } => run_vk_registration(well_formedness, challenge, url, phrase).await?,
+        Command::Vk {
+            command:
+                VkCommand::Status {
+                    well_formedness,
+                    challenge,
+                    url,
+                },
+        } => run_vk_status(well_formedness, challenge, url).await?,
         Command::BuildContract => run_contract_build()?,
         Command::DeployContract {
             challenge,
             reward,
+            e,
+            hashed,
+            factor_count,
+            prove_well_formed,
+            factors,
             url,
             phrase,
-        } => run_contract_deployment(challenge, reward, url, phrase).await?,
+        } => {
+            let factors = match factors.filter(|_| prove_well_formed) {
+                Some(path) => Some(read_factors(&path)?),
+                None => None,
+            };
+            let kind = match factor_count {
+                _ if hashed => ChallengeKind::HashedFactoring,
+                Some(count) => ChallengeKind::MultiFactor(count),
+                None => ChallengeKind::Factoring,
+            };
+            run_contract_deployment(challenge, kind, e, reward, factors, url, phrase).await?
+        }
+        Command::DeployPreimageContract {
+            hash,
+            reward,
+            url,
+            phrase,
+        } => {
+            run_preimage_contract_deployment(parse_field_element(&hash)?, reward, url, phrase)
+                .await?
+        }
         Command::SubmitSolution {
             address,
             url,
             phrase,
         } => run_submission(AccountId32::from_str(&address)?, url, phrase).await?,
+        Command::Play {
+            address,
+            p,
+            q,
+            factors,
+            restart,
+            url,
+            phrase,
+        } => {
+            let factors = match (p.zip(q), factors) {
+                (Some((p, q)), _) => Factors::Given(p, q),
+                (None, Some(path)) => Factors::File(path),
+                (None, None) => Factors::Missing,
+            };
+            run_play(
+                AccountId32::from_str(&address)?,
+                factors,
+                restart,
+                url,
+                phrase,
+            )
+            .await?
+        }
+        Command::Watch { address, json, url } => {
+            run_watch(AccountId32::from_str(&address)?, url, json).await?
+        }
     }
     Ok(())
 }
@@ -58,3 +251,7 @@ async fn main() -> Result<()> {
 fn get_contract_manifest() -> impl Into<PathBuf> {
     Path::new(env!("CARGO_MANIFEST_DIR")).join("../rsa_contract/Cargo.toml")
 }
+
+fn get_contract_metadata() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../rsa_contract/target/ink/rsa_contract.json")
+}
@@ -0,0 +1,206 @@
+use std::{
+    fs::{read_to_string, remove_file, write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use rsa_circuit::{
+    challenge::{ChallengeCircuit, Factoring, HashedFactoring},
+    utils::{Account, Domain, Setup},
+};
+use serde::{Deserialize, Serialize};
+use subxt::utils::AccountId32;
+use url::Url;
+
+use crate::{
+    chain_ops::fetch_domain,
+    contract_ops::{dry_run_solution, read_challenge, submit_solution},
+    read_challenge_setup,
+    signer::signer_from_phrase,
+};
+
+const PLAY_STATE_FILE: &str = "play-state.json";
+
+/// Progress of a single `play` run. It is saved after every completed stage.
+#[derive(Serialize, Deserialize)]
+struct PlayState {
+    /// Contract that is being played.
+    address: String,
+    /// Account that the proof is bound to.
+    account: String,
+    /// The number to factorize, as read from the contract.
+    n: Option<u128>,
+    /// Whether the contract expects proofs in the hashed public input mode.
+    #[serde(default)]
+    hashed: bool,
+    /// Generated SNARK proof.
+    proof: Option<Vec<u8>>,
+    /// Whether the proof has been verified locally.
+    verified: bool,
+    /// Whether the submission has been successfully dry-run.
+    dry_run: bool,
+}
+
+impl PlayState {
+    fn new(address: &AccountId32, account: &AccountId32) -> Self {
+        Self {
+            address: address.to_string(),
+            account: account.to_string(),
+            n: None,
+            hashed: false,
+            proof: None,
+            verified: false,
+            dry_run: false,
+        }
+    }
+
+    /// Load the saved progress for playing `address` as `account`, or start from scratch.
+    fn load_or_new(address: &AccountId32, account: &AccountId32, restart: bool) -> Result<Self> {
+        if restart || !Path::new(PLAY_STATE_FILE).exists() {
+            return Ok(Self::new(address, account));
+        }
+
+        let saved: Self = serde_json::from_str(&read_to_string(PLAY_STATE_FILE)?)
+            .context("Failed to parse saved progress")?;
+        if saved.address != address.to_string() || saved.account != account.to_string() {
+            bail!(
+                "`{PLAY_STATE_FILE}` contains progress for a different contract or account, use `--restart` to discard it"
+            );
+        }
+        println!("⏭️  Resuming from `{PLAY_STATE_FILE}`");
+        Ok(saved)
+    }
+
+    fn save(&self) -> Result<()> {
+        write(PLAY_STATE_FILE, serde_json::to_string_pretty(self)?)
+            .context("Failed to save progress")
+    }
+}
+
+/// Proof generation for a factoring challenge in either public input mode (see `ChallengeCircuit::generate_proof`).
+type Prover = fn(&Setup, &u128, &(u128, u128), Account, Domain) -> Option<Vec<u8>>;
+/// Proof verification for a factoring challenge in either public input mode (see `ChallengeCircuit::verify_proof`).
+type Verifier = fn(&Setup, &[u8], &u128, Account, Domain) -> bool;
+
+/// Factors of the challenge, either given directly or through a file.
+pub enum Factors {
+    Given(u128, u128),
+    File(PathBuf),
+    Missing,
+}
+
+impl Factors {
+    fn resolve(self) -> Result<(u128, u128)> {
+        match self {
+            Factors::Given(p, q) => Ok((p, q)),
+            Factors::File(path) => read_factors(&path),
+            Factors::Missing => bail!("Factors are required to generate the proof"),
+        }
+    }
+}
+
+/// Read two whitespace-separated factors from the file at `path`.
+pub fn read_factors(path: &Path) -> Result<(u128, u128)> {
+    let content = read_to_string(path)
+        .with_context(|| format!("Failed to read factors from `{}`", path.display()))?;
+    let factors = content
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<Vec<u128>, _>>()
+        .context("Factors must be decimal numbers")?;
+    match factors[..] {
+        [p, q] => Ok((p, q)),
+        _ => bail!("Expected exactly two factors in `{}`", path.display()),
+    }
+}
+
+pub async fn run_play(
+    address: AccountId32,
+    factors: Factors,
+    restart: bool,
+    url: Url,
+    phrase: String,
+) -> Result<()> {
+    let account = signer_from_phrase(phrase.clone())?
+        .public_key()
+        .to_account_id();
+    let mut state = PlayState::load_or_new(&address, &account, restart)?;
+
+    let n = match state.n {
+        Some(n) => n,
+        None => {
+            println!("⏳ Reading challenge...");
+            let (n, hashed) = read_challenge(address.clone(), url.clone(), phrase.clone()).await?;
+            state.n = Some(n);
+            state.hashed = hashed;
+            state.save()?;
+            n
+        }
+    };
+    if state.hashed {
+        println!("🎯 Challenge: \x1b[1m{n}\x1b[0m (hashed public input mode)");
+    } else {
+        println!("🎯 Challenge: \x1b[1m{n}\x1b[0m");
+    }
+
+    let account = Account::new(account.0);
+    let domain = fetch_domain(&address, url.clone()).await?;
+    let (setup, prove, verify): (Setup, Prover, Verifier) = if state.hashed {
+        (
+            read_challenge_setup::<HashedFactoring>()?,
+            HashedFactoring::generate_proof,
+            HashedFactoring::verify_proof,
+        )
+    } else {
+        (
+            read_challenge_setup::<Factoring>()?,
+            Factoring::generate_proof,
+            Factoring::verify_proof,
+        )
+    };
+    let proof = match state.proof.clone() {
+        Some(proof) => proof,
+        None => {
+            let (p, q) = factors.resolve()?;
+
+            println!("⏳ Generating SNARK proof...");
+            let Some(proof) = prove(&setup, &n, &(p, q), account, domain) else {
+                bail!("{p} * {q} is not a non-trivial factorization of the challenge {n}");
+            };
+            println!("✅ Generated SNARK proof");
+            state.proof = Some(proof.clone());
+            state.save()?;
+            proof
+        }
+    };
+
+    if !state.verified {
+        println!("⏳ Verifying SNARK proof locally...");
+        ensure!(
+            verify(&setup, &proof, &n, account, domain),
+            "Generated proof is invalid, run again with `--restart`"
+        );
+        println!("✅ SNARK proof is valid");
+        state.verified = true;
+        state.save()?;
+    }
+
+    if !state.dry_run {
+        println!("⏳ Dry-running submission...");
+        dry_run_solution(address.clone(), proof.clone(), url.clone(), phrase.clone()).await?;
+        println!("✅ Dry run succeeded");
+        state.dry_run = true;
+        state.save()?;
+    }
+
+    println!("⏳ Submitting solution...");
+    let solved = submit_solution(address, proof, url, phrase).await?;
+    remove_file(PLAY_STATE_FILE).context("Failed to remove saved progress")?;
+
+    if solved {
+        println!("✅ \x1b[1mChallenge solved!\x1b[0m");
+    } else {
+        println!("❌ \x1b[1mChallenge not solved, proof found to be incorrect\x1b[0m");
+    }
+    Ok(())
+}
@@ -0,0 +1,264 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use contract_transcode::{ContractMessageTranscoder, Value as ContractValue};
+use futures::StreamExt;
+use serde::Serialize;
+use subxt::{
+    blocks::Block,
+    ext::codec::Decode,
+    utils::{AccountId32, H256},
+    OnlineClient, PolkadotConfig,
+};
+use url::Url;
+
+use crate::get_contract_metadata;
+
+const CONTRACTS_PALLET: &str = "Contracts";
+const CONTRACT_EMITTED_EVENT: &str = "ContractEmitted";
+const CONTRACT_TERMINATED_EVENT: &str = "Terminated";
+
+/// Delay before the first reconnection attempt. It is doubled after every consecutive failure.
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+type Api = OnlineClient<PolkadotConfig>;
+
+/// Contract activity that is reported to the user.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Activity {
+    /// Someone submitted a proof that was rejected.
+    Attempt { block: u32 },
+    /// Someone submitted a correct proof.
+    Win { block: u32 },
+    /// The contract was terminated and the reward was transferred to `beneficiary`.
+    Termination { block: u32, beneficiary: String },
+}
+
+struct Watcher {
+    address: AccountId32,
+    transcoder: ContractMessageTranscoder,
+    json: bool,
+    /// The last finalized block that has been fully processed.
+    last_block: Option<u32>,
+}
+
+pub async fn run_watch(address: AccountId32, url: Url, json: bool) -> Result<()> {
+    let transcoder = ContractMessageTranscoder::load(get_contract_metadata())
+        .context("Failed to load contract metadata (have you built the contract?)")?;
+    let mut watcher = Watcher {
+        address,
+        transcoder,
+        json,
+        last_block: None,
+    };
+
+    let mut delay = INITIAL_RECONNECT_DELAY;
+    loop {
+        match watcher.follow(&url, &mut delay).await {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                eprintln!(
+                    "⚠️  Lost connection to `{url}`: {err:#}. Reconnecting in {}s...",
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        }
+    }
+}
+
+impl Watcher {
+    /// Follow finalized blocks until the contract is terminated. Returns an error if the connection breaks.
+    async fn follow(&mut self, url: &Url, delay: &mut Duration) -> Result<()> {
+        let api = Api::from_url(url).await?;
+        let mut blocks = api.blocks().subscribe_finalized().await?;
+        *delay = INITIAL_RECONNECT_DELAY;
+        self.status(format!(
+            "👀 Watching contract {} at `{url}`...",
+            self.address
+        ));
+
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let number = block.number();
+            if self.last_block.map_or(false, |last| number <= last) {
+                continue;
+            }
+
+            // Catch up on the blocks that were finalized while we were disconnected.
+            if let Some(last) = self.last_block {
+                for missed in last + 1..number {
+                    let hash = api
+                        .rpc()
+                        .block_hash(Some(missed.into()))
+                        .await?
+                        .with_context(|| format!("Missing hash of finalized block #{missed}"))?;
+                    if self.process(api.blocks().at(hash).await?).await? {
+                        return Ok(());
+                    }
+                }
+            }
+
+            if self.process(block).await? {
+                return Ok(());
+            }
+        }
+        bail!("Block subscription closed")
+    }
+
+    /// Report all the contract activity from `block`. Returns `true` if the contract has been terminated.
+    async fn process(&mut self, block: Block<PolkadotConfig, Api>) -> Result<bool> {
+        let number = block.number();
+        let mut terminated = false;
+
+        for event in block.events().await?.iter() {
+            let event = event?;
+            if event.pallet_name() != CONTRACTS_PALLET {
+                continue;
+            }
+
+            match event.variant_name() {
+                CONTRACT_EMITTED_EVENT => {
+                    // A malformed event must not stop the watcher (nor be mistaken for a lost connection).
+                    let decoded = match self.decode_emitted(event.field_bytes(), event.topics()) {
+                        Ok(Some(decoded)) => decoded,
+                        Ok(None) => continue,
+                        Err(err) => {
+                            self.status(format!(
+                                "❔ #{number}: undecodable contract event: {err:#}"
+                            ));
+                            continue;
+                        }
+                    };
+                    match event_name(&decoded).as_deref() {
+                        Some("ChallengeSolved") => self.report(Activity::Win { block: number }),
+                        Some("ChallengeStillTooHard") => {
+                            self.report(Activity::Attempt { block: number })
+                        }
+                        _ => self.status(format!("❔ #{number}: unknown contract event {decoded}")),
+                    }
+                }
+                CONTRACT_TERMINATED_EVENT => {
+                    let (contract, beneficiary) =
+                        <(AccountId32, AccountId32)>::decode(&mut event.field_bytes())?;
+                    if contract == self.address {
+                        self.report(Activity::Termination {
+                            block: number,
+                            beneficiary: beneficiary.to_string(),
+                        });
+                        terminated = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.last_block = Some(number);
+        Ok(terminated)
+    }
+
+    /// Decode the fields of a `ContractEmitted` event. Returns `None` if it was emitted by another contract.
+    fn decode_emitted(&self, mut fields: &[u8], topics: &[H256]) -> Result<Option<ContractValue>> {
+        let contract = AccountId32::decode(&mut fields)?;
+        if contract != self.address {
+            return Ok(None);
+        }
+        // The first topic of an ink! event is its signature. The remaining fields are the event data as `Vec<u8>`,
+        // whose length prefix is read by the transcoder.
+        let signature = topics
+            .first()
+            .context("Contract event without a signature topic")?;
+        self.transcoder
+            .decode_contract_event(signature, &mut fields)
+            .map(Some)
+    }
+
+    fn report(&self, activity: Activity) {
+        if self.json {
+            println!(
+                "{}",
+                serde_json::to_string(&activity).expect("Activity should be serializable")
+            );
+            return;
+        }
+        match activity {
+            Activity::Attempt { block } => println!("❌ #{block}: solution rejected"),
+            Activity::Win { block } => println!("✅ #{block}: \x1b[1mChallenge solved!\x1b[0m"),
+            Activity::Termination { block, beneficiary } => println!(
+                "🏁 #{block}: contract terminated, reward transferred to \x1b[1m{beneficiary}\x1b[0m"
+            ),
+        }
+    }
+
+    /// Print a human-readable status line. In JSON mode it goes to stderr, so that stdout stays parsable.
+    fn status(&self, message: String) {
+        if self.json {
+            eprintln!("{message}");
+        } else {
+            println!("{message}");
+        }
+    }
+}
+
+fn event_name(event: &ContractValue) -> Option<String> {
+    match event {
+        ContractValue::Map(map) => map.ident(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use subxt::ext::codec::Encode;
+
+    use super::*;
+
+    const CONTRACT: AccountId32 = AccountId32([1; 32]);
+
+    fn watcher() -> Watcher {
+        Watcher {
+            address: CONTRACT,
+            transcoder: ContractMessageTranscoder::load(get_contract_metadata())
+                .expect("Contract metadata should be available (run `build-contract` first)"),
+            json: false,
+            last_block: None,
+        }
+    }
+
+    /// Signature topic of the contract event `label`, as found in the metadata.
+    fn signature_topic(watcher: &Watcher, label: &str) -> H256 {
+        let event = watcher
+            .transcoder
+            .metadata()
+            .spec()
+            .events()
+            .iter()
+            .find(|event| event.label() == label)
+            .expect("Event should be in the metadata");
+        H256::from_slice(
+            event
+                .signature_topic()
+                .expect("Event should have a signature topic")
+                .as_bytes(),
+        )
+    }
+
+    #[test]
+    fn decodes_contract_events() {
+        let watcher = watcher();
+        for label in ["ChallengeSolved", "ChallengeStillTooHard"] {
+            let topics = [signature_topic(&watcher, label)];
+            // Fields of `Contracts::ContractEmitted`: the contract and the (field-less) event data.
+            let fields = (CONTRACT, Vec::<u8>::new()).encode();
+
+            let decoded = watcher.decode_emitted(&fields, &topics).unwrap().unwrap();
+            assert_eq!(event_name(&decoded).as_deref(), Some(label));
+
+            let other = (AccountId32([2; 32]), Vec::<u8>::new()).encode();
+            assert!(watcher.decode_emitted(&other, &topics).unwrap().is_none());
+        }
+    }
+}
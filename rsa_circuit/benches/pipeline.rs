@@ -0,0 +1,75 @@
+//! Costs of the whole pipeline (setup, proving, verification and setup serialization), how they scale with `k` and how
+//! they depend on the multi-open scheme.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rsa_circuit::{
+    utils::{
+        generate_proof, generate_setup, verify_proof, Account, Domain, MultiOpenScheme, Setup,
+    },
+    CIRCUIT_MAX_K,
+};
+
+const KS: [u32; 4] = [CIRCUIT_MAX_K, 8, 11, 14];
+const SCHEMES: [MultiOpenScheme; 2] = [MultiOpenScheme::Gwc, MultiOpenScheme::Shplonk];
+const ACCOUNT: Account = Account::new([1; 32]);
+const DOMAIN: Domain = Domain {
+    contract: Account::new([2; 32]),
+    chain_id: [3; 32],
+};
+
+fn setup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("setup");
+    group.sample_size(10);
+    for k in KS {
+        group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, k| {
+            b.iter(|| generate_setup(*k, MultiOpenScheme::Gwc))
+        });
+    }
+    group.finish();
+}
+
+fn serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialization");
+    for k in KS {
+        let setup = generate_setup(k, MultiOpenScheme::Gwc);
+        let serialized = setup.clone().to_bytes();
+        group.bench_with_input(BenchmarkId::new("to_bytes", k), &setup, |b, setup| {
+            b.iter(|| setup.clone().to_bytes())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("from_bytes", k),
+            &serialized,
+            |b, bytes| b.iter(|| Setup::from_bytes(&mut bytes.as_slice()).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn proving_and_verification(c: &mut Criterion) {
+    let mut proving = c.benchmark_group("proving");
+    proving.sample_size(10);
+    let mut inputs = vec![];
+    for k in KS {
+        for scheme in SCHEMES {
+            let setup = generate_setup(k, scheme);
+            let id = BenchmarkId::new(format!("{scheme:?}"), k);
+            proving.bench_with_input(id.clone(), &setup, |b, setup| {
+                b.iter(|| generate_proof(setup, 41, 43, ACCOUNT, DOMAIN))
+            });
+            let proof = generate_proof(&setup, 41, 43, ACCOUNT, DOMAIN);
+            inputs.push((id, setup, proof));
+        }
+    }
+    proving.finish();
+
+    let mut verification = c.benchmark_group("native verification");
+    for (id, setup, proof) in inputs {
+        verification.bench_with_input(id, &(setup, proof), |b, (setup, proof)| {
+            b.iter(|| verify_proof(setup, proof, 41 * 43, ACCOUNT, DOMAIN))
+        });
+    }
+    verification.finish();
+}
+
+criterion_group!(benches, setup, serialization, proving_and_verification);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+//! Comparison of verifying proofs one by one and in a batch.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rsa_circuit::{
+    batch::Submission,
+    utils::{
+        generate_proof, generate_setup, verify_batch, verify_proof, Account, Domain,
+        MultiOpenScheme,
+    },
+    CIRCUIT_MAX_K,
+};
+
+const BATCH_SIZES: [usize; 4] = [1, 4, 16, 64];
+const DOMAIN: Domain = Domain {
+    contract: Account::new([0xff; 32]),
+    chain_id: [0; 32],
+};
+
+fn verification(c: &mut Criterion) {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let submissions = (0..*BATCH_SIZES.iter().max().unwrap())
+        .map(|i| {
+            let account = Account::new([i as u8; 32]);
+            (
+                generate_proof(&setup, 41, 43, account, DOMAIN),
+                41 * 43,
+                account,
+            )
+        })
+        .collect::<Vec<Submission>>();
+
+    let mut group = c.benchmark_group("verification");
+    for size in BATCH_SIZES {
+        let batch = &submissions[..size];
+        group.bench_with_input(BenchmarkId::new("one-by-one", size), batch, |b, batch| {
+            b.iter(|| {
+                batch
+                    .iter()
+                    .all(|(proof, n, account)| verify_proof(&setup, proof, *n, *account, DOMAIN))
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", size), batch, |b, batch| {
+            b.iter(|| verify_batch(&setup, DOMAIN, batch))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verification);
+criterion_main!(benches);
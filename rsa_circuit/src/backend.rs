@@ -0,0 +1,264 @@
+//! Choice of the polynomial commitment backend.
+//!
+//! Apart from KZG (which requires a trusted setup and is the only backend supported by the on-chain verifier), the
+//! circuit can be used with the transparent inner product argument (IPA). IPA works over the same curve (BN254 `G1`),
+//! so the circuit, the public input and the keys stay the same; only the parameters and the proofs differ. IPA proofs
+//! are bigger and their verification is linear in the table size, but there is no toxic waste at all.
+
+use std::io;
+
+use halo2_proofs::{
+    arithmetic::{g_to_lagrange, CurveAffine},
+    halo2curves::{
+        bn256::{Fq, G1Affine, G1},
+        ff::{Field, FromUniformBytes},
+        group::GroupEncoding,
+    },
+    plonk::{self, create_proof, keygen_pk, keygen_vk, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::Params,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy,
+        },
+    },
+    standard_plonk::StandardPlonk,
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand::rngs::OsRng;
+
+use crate::{
+    utils::{self, prepare_public_input, Account, Domain, MultiOpenScheme, Setup, SERDE_FORMAT},
+    RsaChallenge,
+};
+
+/// Personalization of the hash used for deriving the IPA generators.
+const GENERATORS_PERSONALIZATION: &[u8; 16] = b"RsaChallengeIPA_";
+
+/// Polynomial commitment backend.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// KZG with the (mock) trusted setup. This is the one expected by the on-chain verifier.
+    #[default]
+    Kzg,
+    /// Inner product argument, no trusted setup.
+    Ipa,
+}
+
+impl Backend {
+    fn to_byte(self) -> u8 {
+        match self {
+            Backend::Kzg => 0,
+            Backend::Ipa => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Backend::Kzg),
+            1 => Some(Backend::Ipa),
+            _ => None,
+        }
+    }
+}
+
+/// Setup artifacts for the IPA backend: transparent parameters, proving key and verifying key.
+#[derive(Clone, Debug)]
+pub struct IpaSetup {
+    /// Logarithm of the maximum number of rows in the PLONK table (polynomial degree).
+    pub k: u32,
+    /// Proving key.
+    pub pk: ProvingKey<G1Affine>,
+    /// Verifying key.
+    pub vk: VerifyingKey<G1Affine>,
+    /// Transparent parameters (commitment generators).
+    pub params: ParamsIPA<G1Affine>,
+}
+
+impl IpaSetup {
+    /// Serialize setup to raw bytes.
+    pub fn to_bytes(self) -> Vec<u8> {
+        let mut buffer = vec![];
+        self.params
+            .write(&mut buffer)
+            .expect("Failed to save IPA parameters");
+        buffer.extend(self.pk.to_bytes(SERDE_FORMAT));
+        buffer
+    }
+
+    /// Deserialize setup from raw bytes.
+    pub fn from_bytes(buffer: &mut &[u8]) -> io::Result<Self> {
+        let params = ParamsIPA::<G1Affine>::read(buffer)?;
+        let pk = ProvingKey::<G1Affine>::from_bytes::<StandardPlonk>(buffer, SERDE_FORMAT)?;
+        Ok(Self {
+            k: params.k(),
+            vk: pk.get_vk().clone(),
+            pk,
+            params,
+        })
+    }
+}
+
+/// Setup for any of the backends.
+#[derive(Clone, Debug)]
+pub enum BackendSetup {
+    /// KZG setup.
+    Kzg(Setup),
+    /// IPA setup.
+    Ipa(IpaSetup),
+}
+
+impl BackendSetup {
+    /// Run the setup for `backend`. For KZG, this is the same as [`utils::generate_setup`].
+    pub fn generate(k: u32, backend: Backend) -> Self {
+        match backend {
+            Backend::Kzg => BackendSetup::Kzg(utils::generate_setup(k, MultiOpenScheme::Gwc)),
+            Backend::Ipa => BackendSetup::Ipa(generate_ipa_setup(k)),
+        }
+    }
+
+    /// Backend of this setup.
+    pub fn backend(&self) -> Backend {
+        match self {
+            BackendSetup::Kzg(_) => Backend::Kzg,
+            BackendSetup::Ipa(_) => Backend::Ipa,
+        }
+    }
+
+    /// Serialize setup to raw bytes: a single byte identifying the backend followed by the backend-specific setup.
+    pub fn to_bytes(self) -> Vec<u8> {
+        let mut buffer = vec![self.backend().to_byte()];
+        buffer.extend(match self {
+            BackendSetup::Kzg(setup) => setup.to_bytes(),
+            BackendSetup::Ipa(setup) => setup.to_bytes(),
+        });
+        buffer
+    }
+
+    /// Deserialize setup from raw bytes.
+    pub fn from_bytes(buffer: &mut &[u8]) -> io::Result<Self> {
+        let bytes: &[u8] = *buffer;
+        let Some((backend, rest)) = bytes.split_first() else {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        };
+        let backend = Backend::from_byte(*backend)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Unknown backend"))?;
+        *buffer = rest;
+
+        Ok(match backend {
+            Backend::Kzg => BackendSetup::Kzg(Setup::from_bytes(buffer)?),
+            Backend::Ipa => BackendSetup::Ipa(IpaSetup::from_bytes(buffer)?),
+        })
+    }
+
+    /// Generate proof given `p`, `q`, `account` and `domain`.
+    pub fn generate_proof(&self, p: u128, q: u128, account: Account, domain: Domain) -> Vec<u8> {
+        match self {
+            BackendSetup::Kzg(setup) => utils::generate_proof(setup, p, q, account, domain),
+            BackendSetup::Ipa(setup) => generate_ipa_proof(setup, p, q, account, domain),
+        }
+    }
+
+    /// Verify `proof` that `account` knows the factorization of `n` challenged by the deployment `domain`.
+    pub fn verify_proof(&self, proof: &[u8], n: u128, account: Account, domain: Domain) -> bool {
+        match self {
+            BackendSetup::Kzg(setup) => utils::verify_proof(setup, proof, n, account, domain),
+            BackendSetup::Ipa(setup) => verify_ipa_proof(setup, proof, n, account, domain),
+        }
+    }
+}
+
+/// Generate the transparent parameters and process the circuit.
+pub fn generate_ipa_setup(k: u32) -> IpaSetup {
+    let circuit = RsaChallenge::default();
+    let params = transparent_params(k);
+    let vk = keygen_vk(&params, &circuit).expect("vk generation should not fail");
+    let pk = keygen_pk(&params, vk.clone(), &circuit).expect("pk generation should not fail");
+    IpaSetup { k, pk, vk, params }
+}
+
+/// Generate IPA proof given `setup`, `p`, `q`, `account` and `domain`.
+pub fn generate_ipa_proof(
+    setup: &IpaSetup,
+    p: u128,
+    q: u128,
+    account: Account,
+    domain: Domain,
+) -> Vec<u8> {
+    let instances = prepare_public_input(p * q, account, domain);
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<'_, _>, _, _, _, _>(
+        &setup.params,
+        &setup.pk,
+        &[RsaChallenge::new(p, q)],
+        &[&[&instances]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("Failed to generate proof");
+    transcript.finalize()
+}
+
+/// Verify IPA `proof` that `account` knows the factorization of `n` challenged by the deployment `domain`.
+pub fn verify_ipa_proof(
+    setup: &IpaSetup,
+    proof: &[u8],
+    n: u128,
+    account: Account,
+    domain: Domain,
+) -> bool {
+    let instances = prepare_public_input(n, account, domain);
+    plonk::verify_proof::<IPACommitmentScheme<_>, VerifierIPA<'_, _>, _, _, _>(
+        &setup.params,
+        &setup.vk,
+        SingleStrategy::new(&setup.params),
+        &[&[&instances]],
+        &mut Blake2bRead::<_, _, Challenge255<_>>::init(proof),
+    )
+    .is_ok()
+}
+
+/// Derive `2^k` commitment generators (and the two auxiliary ones) in the same way as `ParamsIPA::new`, but with our
+/// own hash to curve (the curve library doesn't implement one for BN254).
+///
+/// Since the parameters have no constructor taking the generators directly, they are assembled in the serialized form.
+fn transparent_params(k: u32) -> ParamsIPA<G1Affine> {
+    let n = 1u64 << k;
+    let g = (0..n)
+        .map(|i| hash_to_curve(&i.to_le_bytes()))
+        .collect::<Vec<_>>();
+    let g_lagrange = g_to_lagrange(g.iter().map(|point| G1::from(*point)).collect(), k);
+    let w = hash_to_curve(&[1]);
+    let u = hash_to_curve(&[2]);
+
+    let mut buffer = k.to_le_bytes().to_vec();
+    for point in g.iter().chain(&g_lagrange).chain([&w, &u]) {
+        buffer.extend(point.to_bytes().as_ref());
+    }
+    ParamsIPA::read(&mut buffer.as_slice()).expect("Generated parameters should be valid")
+}
+
+/// Map `message` to a point with an unknown discrete logarithm (try-and-increment).
+///
+/// Every candidate `x` is a hash of `message` and a counter. The first one for which `x^3 + b` is a square gives the
+/// point. BN254 `G1` has cofactor 1, so every curve point belongs to the group.
+pub(crate) fn hash_to_curve(message: &[u8]) -> G1Affine {
+    for counter in 0u32.. {
+        let hash = blake2b_simd::Params::new()
+            .hash_length(64)
+            .personal(GENERATORS_PERSONALIZATION)
+            .to_state()
+            .update(message)
+            .update(&counter.to_le_bytes())
+            .finalize();
+        let x = Fq::from_uniform_bytes(hash.as_array());
+        let y_squared = x.square() * x + G1Affine::b();
+        if let Some(y) = Option::<Fq>::from(y_squared.sqrt()) {
+            return G1Affine::from_xy(x, y).expect("Point should be on the curve");
+        }
+    }
+    unreachable!("Half of the candidates give a point")
+}
@@ -0,0 +1,74 @@
+//! Batch verification of multiple RSA challenge proofs.
+//!
+//! The most expensive part of verifying a KZG proof is the final pairing check. Proofs generated with the same setup
+//! can defer these checks and fold them (with random coefficients) into a single accumulator, so that verifying many
+//! proofs requires just one pairing check (see [`verify_batch`](crate::utils::verify_batch)).
+
+use halo2_proofs::{
+    halo2curves::bn256::Bn256,
+    plonk::{self, Error},
+    poly::{
+        kzg::{
+            multiopen::{VerifierGWC, VerifierSHPLONK},
+            strategy::AccumulatorStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer},
+};
+
+use crate::utils::{prepare_public_input, Account, Domain, MultiOpenScheme, Setup};
+
+/// A single proof (with the default transcript) together with the statement it proves: the challenge `n` and the
+/// account it is bound to. The deployment (domain) is common to all the proofs checked together.
+pub type Submission = (Vec<u8>, u128, Account);
+
+/// Accumulator of deferred pairing checks of multiple proofs.
+pub struct Accumulator<'a> {
+    setup: &'a Setup,
+    domain: Domain,
+    strategy: AccumulatorStrategy<'a, Bn256>,
+}
+
+impl<'a> Accumulator<'a> {
+    /// Create an empty accumulator for proofs generated with `setup` for the deployment `domain`.
+    pub fn new(setup: &'a Setup, domain: Domain) -> Self {
+        Self {
+            setup,
+            domain,
+            strategy: AccumulatorStrategy::new(&setup.params),
+        }
+    }
+
+    /// Run all the checks of `proof` except for the final pairing, which is folded into the accumulator.
+    ///
+    /// An error means that the proof is malformed (and thus invalid). In that case the accumulator is left unchanged.
+    pub fn fold(&mut self, proof: &[u8], n: u128, account: Account) -> Result<(), Error> {
+        let instances = prepare_public_input(n, account, self.domain);
+        let strategy = self.strategy.clone();
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        self.strategy = match self.setup.scheme {
+            MultiOpenScheme::Gwc => plonk::verify_proof::<_, VerifierGWC<_>, _, _, _>(
+                &self.setup.params,
+                &self.setup.vk,
+                strategy,
+                &[&[&instances]],
+                &mut transcript,
+            ),
+            MultiOpenScheme::Shplonk => plonk::verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+                &self.setup.params,
+                &self.setup.vk,
+                strategy,
+                &[&[&instances]],
+                &mut transcript,
+            ),
+        }?;
+        Ok(())
+    }
+
+    /// Perform the single, accumulated pairing check. Returns `true` iff all the folded proofs are valid (except for a
+    /// negligible probability).
+    pub fn check(self) -> bool {
+        self.strategy.finalize()
+    }
+}
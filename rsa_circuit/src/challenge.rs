@@ -0,0 +1,361 @@
+//! Common interface of the puzzles that a challenge can be built around.
+//!
+//! Every puzzle consists of a public statement (e.g. the number to factorize), a secret witness solving it (e.g. the
+//! factors) and a circuit proving that the witness solves the statement. Apart from the statement, the public input
+//! always binds the proof to the participant and the deployment, so proofs of any puzzle are generated and verified in
+//! the same way, and the contract only has to know how to encode the statement.
+//!
+//! [`Factoring`] is the original RSA challenge and [`HashedFactoring`] is its hashed public input mode (see
+//! [`hashed`](crate::hashed)). [`MultiFactor`] generalizes it to more factors (see
+//! [`multi_factor`](crate::multi_factor)), [`PrivateKey`] is the challenge to find a private exponent of an RSA public
+//! key (see [`private_key`](crate::private_key)) and [`HashPreimage`] is the challenge to find a preimage of a Poseidon
+//! digest (see [`preimage`](crate::preimage)).
+//!
+//! [`WellFormedness`] is not a puzzle of its own, but the proof that the deployer of an RSA challenge gives about `n`
+//! (see [`well_formedness`](crate::well_formedness)). It goes through the same pipeline, but its public input is not
+//! bound to any participant or deployment.
+
+#[cfg(feature = "dev-graph")]
+use std::{io, path::Path};
+
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        ff::PrimeField,
+    },
+    plonk::Circuit,
+    poly::kzg::commitment::ParamsKZG,
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer},
+};
+use rsa_public_input::FieldElementBytes;
+
+use crate::{
+    hashed::{HashedRsaChallenge, HASHED_CIRCUIT_K},
+    info::{self, CircuitInfo},
+    multi_factor::{self, MultiFactorChallenge, MAX_FACTOR_COUNT, MULTI_FACTOR_K},
+    preimage::{PreimageChallenge, PREIMAGE_K},
+    private_key::{PrivateKeyChallenge, PublicKey, PRIVATE_KEY_K},
+    utils::{self, Account, Domain, MultiOpenScheme, Setup},
+    well_formedness::{self, Semiprime, WellFormednessChallenge, WELL_FORMEDNESS_K},
+    RsaChallenge, CIRCUIT_MAX_K,
+};
+
+/// Names of the [`MultiFactor`] challenges, indexed by the number of factors (there are none with fewer than two).
+const MULTI_FACTOR_NAMES: [&str; MAX_FACTOR_COUNT + 1] = [
+    "", "", "2-factor", "3-factor", "4-factor", "5-factor", "6-factor", "7-factor", "8-factor",
+];
+
+/// A puzzle together with the circuit proving its solutions.
+///
+/// Only [`encode`](ChallengeCircuit::encode) and the circuit construction have to be implemented, the rest of the
+/// pipeline (setup, proving and verification) is shared by all the puzzles.
+pub trait ChallengeCircuit {
+    /// Short name of the puzzle, e.g. for file names.
+    const NAME: &'static str;
+
+    /// Logarithm of the number of rows in the PLONK table.
+    const K: u32;
+
+    /// Public statement of the puzzle, e.g. the number to factorize.
+    type Statement;
+
+    /// Secret solution of the puzzle, e.g. the factors.
+    type Witness;
+
+    /// Circuit proving the knowledge of a witness for a statement. It must use the standard PLONK configuration, as
+    /// this is what the on-chain verifier assumes.
+    type Circuit: Circuit<Fr>;
+
+    /// Create the circuit without witnesses (e.g. for key generation).
+    fn empty_circuit() -> Self::Circuit;
+
+    /// Create the circuit with `witness`, for the proof of `account` challenged by the deployment `domain` (only the
+    /// circuits taking the context as a private input use them). Returns `None` if the witness doesn't solve
+    /// `statement`.
+    fn circuit(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        account: &Account,
+        domain: &Domain,
+    ) -> Option<Self::Circuit>;
+
+    /// Encode `statement`, `account` and `domain` as field elements, in the order of the instances of the circuit.
+    fn encode(
+        statement: &Self::Statement,
+        account: &Account,
+        domain: &Domain,
+    ) -> Vec<FieldElementBytes>;
+
+    /// Convert the public input from human-readable form to scalars.
+    fn prepare_public_input(
+        statement: &Self::Statement,
+        account: Account,
+        domain: Domain,
+    ) -> Vec<Fr> {
+        Self::encode(statement, &account, &domain)
+            .into_iter()
+            .map(|element| Fr::from_repr(element).unwrap())
+            .collect()
+    }
+
+    /// Encode the public input as concatenated field elements, as expected by the on-chain verifier.
+    fn encode_bytes(statement: &Self::Statement, account: &Account, domain: &Domain) -> Vec<u8> {
+        Self::encode(statement, account, domain).concat()
+    }
+
+    /// Generate the setup for the circuit (with the mock SRS, as expected by the on-chain verifier), for proofs using
+    /// the multi-open `scheme`.
+    fn generate_setup(scheme: MultiOpenScheme) -> Setup {
+        Setup {
+            scheme,
+            ..utils::setup_from_params(
+                ParamsKZG::<Bn256>::setup(Self::K, ParamsKZG::<Bn256>::mock_rng()),
+                &Self::empty_circuit(),
+            )
+        }
+    }
+
+    /// Generate proof that `account` knows `witness` solving `statement` challenged by the deployment `domain`.
+    /// Returns `None` if the witness doesn't solve the statement.
+    fn generate_proof(
+        setup: &Setup,
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        account: Account,
+        domain: Domain,
+    ) -> Option<Vec<u8>> {
+        let circuit = Self::circuit(statement, witness, &account, &domain)?;
+        let instances = Self::prepare_public_input(statement, account, domain);
+        Some(utils::prove(setup, circuit, &instances))
+    }
+
+    /// Verify `proof` that `account` knows a solution of `statement` challenged by the deployment `domain`.
+    fn verify_proof(
+        setup: &Setup,
+        proof: &[u8],
+        statement: &Self::Statement,
+        account: Account,
+        domain: Domain,
+    ) -> bool {
+        utils::verify_with(
+            setup,
+            &mut Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof),
+            &Self::prepare_public_input(statement, account, domain),
+        )
+    }
+
+    /// Measure the cost of the circuit (see [`info::measure`]).
+    fn info() -> CircuitInfo {
+        info::measure(Self::K, &Self::empty_circuit())
+    }
+
+    /// Render the layout of the circuit to the file at `path` (see [`info::render_layout`]).
+    #[cfg(feature = "dev-graph")]
+    fn render_layout(path: &Path) -> io::Result<()> {
+        info::render_layout(Self::K, &Self::empty_circuit(), path)
+    }
+}
+
+/// The RSA challenge: find the factors `(p, q)` of `n`.
+pub struct Factoring;
+
+impl ChallengeCircuit for Factoring {
+    const NAME: &'static str = "factoring";
+    const K: u32 = CIRCUIT_MAX_K;
+
+    type Statement = u128;
+    type Witness = (u128, u128);
+    type Circuit = RsaChallenge;
+
+    fn empty_circuit() -> Self::Circuit {
+        RsaChallenge::default()
+    }
+
+    fn circuit(n: &u128, factors: &(u128, u128), _: &Account, _: &Domain) -> Option<Self::Circuit> {
+        is_factorization(*n, factors).then(|| RsaChallenge::new(factors.0, factors.1))
+    }
+
+    fn encode(n: &u128, account: &Account, domain: &Domain) -> Vec<FieldElementBytes> {
+        rsa_public_input::encode(*n, account, domain).to_vec()
+    }
+}
+
+/// The RSA challenge in the hashed public input mode: the same puzzle as [`Factoring`], but the context is hashed into
+/// a single instance.
+pub struct HashedFactoring;
+
+impl ChallengeCircuit for HashedFactoring {
+    const NAME: &'static str = "hashed-factoring";
+    const K: u32 = HASHED_CIRCUIT_K;
+
+    type Statement = u128;
+    type Witness = (u128, u128);
+    type Circuit = HashedRsaChallenge;
+
+    fn empty_circuit() -> Self::Circuit {
+        HashedRsaChallenge::default()
+    }
+
+    fn circuit(
+        n: &u128,
+        factors: &(u128, u128),
+        account: &Account,
+        domain: &Domain,
+    ) -> Option<Self::Circuit> {
+        is_factorization(*n, factors)
+            .then(|| HashedRsaChallenge::new(factors.0, factors.1, *account, *domain))
+    }
+
+    fn encode(n: &u128, account: &Account, domain: &Domain) -> Vec<FieldElementBytes> {
+        rsa_public_input::encode_hashed(*n, account, domain).to_vec()
+    }
+}
+
+/// The multi-factor challenge: find `FACTORS` non-trivial factors of `n`, in any order. Every number of factors (between
+/// 2 and [`MAX_FACTOR_COUNT`]) has its own circuit and verifying key, but the public input is the same as for
+/// [`Factoring`].
+pub struct MultiFactor<const FACTORS: usize>;
+
+impl<const FACTORS: usize> ChallengeCircuit for MultiFactor<FACTORS> {
+    const NAME: &'static str = MULTI_FACTOR_NAMES[FACTORS];
+    const K: u32 = MULTI_FACTOR_K;
+
+    type Statement = u128;
+    type Witness = [u128; FACTORS];
+    type Circuit = MultiFactorChallenge;
+
+    fn empty_circuit() -> Self::Circuit {
+        MultiFactorChallenge::without_factors(FACTORS)
+    }
+
+    fn circuit(
+        n: &u128,
+        factors: &[u128; FACTORS],
+        _: &Account,
+        _: &Domain,
+    ) -> Option<Self::Circuit> {
+        if multi_factor::product(factors)? != *n {
+            return None;
+        }
+        MultiFactorChallenge::new(factors)
+    }
+
+    fn encode(n: &u128, account: &Account, domain: &Domain) -> Vec<FieldElementBytes> {
+        rsa_public_input::encode(*n, account, domain).to_vec()
+    }
+}
+
+/// The private-key challenge: find a private exponent `d` for the public key `(n, e)`. The witness consists of the
+/// factors and the exponent: `(p, q, d)`.
+pub struct PrivateKey;
+
+impl ChallengeCircuit for PrivateKey {
+    const NAME: &'static str = "private-key";
+    const K: u32 = PRIVATE_KEY_K;
+
+    type Statement = PublicKey;
+    type Witness = (u128, u128, u128);
+    type Circuit = PrivateKeyChallenge;
+
+    fn empty_circuit() -> Self::Circuit {
+        PrivateKeyChallenge::default()
+    }
+
+    fn circuit(
+        key: &PublicKey,
+        (p, q, d): &(u128, u128, u128),
+        _: &Account,
+        _: &Domain,
+    ) -> Option<Self::Circuit> {
+        if p.checked_mul(*q) != Some(key.n) {
+            return None;
+        }
+        PrivateKeyChallenge::new(*p, *q, key.e, *d)
+    }
+
+    fn encode(key: &PublicKey, account: &Account, domain: &Domain) -> Vec<FieldElementBytes> {
+        rsa_public_input::encode_private_key(key.n, key.e, account, domain).to_vec()
+    }
+}
+
+/// The hash-preimage challenge: find a preimage `x` of the Poseidon digest `h` (both in little-endian bytes).
+pub struct HashPreimage;
+
+impl ChallengeCircuit for HashPreimage {
+    const NAME: &'static str = "hash-preimage";
+    const K: u32 = PREIMAGE_K;
+
+    type Statement = FieldElementBytes;
+    type Witness = FieldElementBytes;
+    type Circuit = PreimageChallenge;
+
+    fn empty_circuit() -> Self::Circuit {
+        PreimageChallenge::default()
+    }
+
+    fn circuit(
+        hash: &FieldElementBytes,
+        preimage: &FieldElementBytes,
+        _: &Account,
+        _: &Domain,
+    ) -> Option<Self::Circuit> {
+        if rsa_public_input::hash_preimage(preimage)? != *hash {
+            return None;
+        }
+        PreimageChallenge::new(preimage)
+    }
+
+    /// A non-canonical digest has no preimage, so its public input is empty and no proof is accepted.
+    fn encode(
+        hash: &FieldElementBytes,
+        account: &Account,
+        domain: &Domain,
+    ) -> Vec<FieldElementBytes> {
+        rsa_public_input::encode_preimage(hash, account, domain)
+            .map(|elements| elements.to_vec())
+            .unwrap_or_default()
+    }
+}
+
+/// The proof that `n` is a product of two primes with the stated number of bits each. The witness are the factors.
+pub struct WellFormedness;
+
+impl ChallengeCircuit for WellFormedness {
+    const NAME: &'static str = "well-formedness";
+    const K: u32 = WELL_FORMEDNESS_K;
+
+    type Statement = Semiprime;
+    type Witness = (u128, u128);
+    type Circuit = WellFormednessChallenge;
+
+    fn empty_circuit() -> Self::Circuit {
+        WellFormednessChallenge::default()
+    }
+
+    fn circuit(
+        semiprime: &Semiprime,
+        (p, q): &(u128, u128),
+        _: &Account,
+        _: &Domain,
+    ) -> Option<Self::Circuit> {
+        if p.checked_mul(*q) != Some(semiprime.n)
+            || well_formedness::bit_length(*p) != Some(semiprime.factor_bits)
+        {
+            return None;
+        }
+        WellFormednessChallenge::new(*p, *q)
+    }
+
+    /// The public input doesn't depend on `account` nor `domain`. With an unsupported bit length, it is empty and no
+    /// proof is accepted.
+    fn encode(semiprime: &Semiprime, _: &Account, _: &Domain) -> Vec<FieldElementBytes> {
+        rsa_public_input::encode_well_formedness(semiprime.n, semiprime.factor_bits)
+            .map(|elements| elements.to_vec())
+            .unwrap_or_default()
+    }
+}
+
+/// Whether `p * q = n` with both factors greater than 1.
+fn is_factorization(n: u128, &(p, q): &(u128, u128)) -> bool {
+    p > 1 && q > 1 && p.checked_mul(q) == Some(n)
+}
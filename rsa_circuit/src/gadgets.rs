@@ -0,0 +1,216 @@
+//! Building blocks for circuits made of the standard PLONK gate only.
+//!
+//! The on-chain verifier reads every verifying key as [`StandardPlonk`](halo2_proofs::standard_plonk::StandardPlonk)
+//! one, so any additional circuit has to express its relation with the single gate
+//! `q_a*a + q_b*b + q_c*c + q_ab*a*b + constant + instance = 0`. The helpers below assign such gates row by row.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{AssignedCell, Region, Value},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::Error,
+    standard_plonk::StandardPlonkConfig,
+};
+
+/// Value assigned to an advice cell.
+pub(crate) type Number = AssignedCell<Fr, Fr>;
+
+/// Value of a single advice cell of the gate.
+pub(crate) enum Input<'n> {
+    /// New witness.
+    Fresh(Value<Fr>),
+    /// Copy of an already assigned value (constrained to be equal).
+    Copy(&'n Number),
+}
+
+/// Coefficients of the gate (fixed columns): `q_a*a + q_b*b + q_c*c + q_ab*a*b + constant = 0`.
+#[derive(Clone, Copy)]
+pub(crate) struct Coefficients {
+    pub(crate) a: Fr,
+    pub(crate) b: Fr,
+    pub(crate) c: Fr,
+    pub(crate) ab: Fr,
+    pub(crate) constant: Fr,
+}
+
+impl Default for Coefficients {
+    fn default() -> Self {
+        Self {
+            a: Fr::zero(),
+            b: Fr::zero(),
+            c: Fr::zero(),
+            ab: Fr::zero(),
+            constant: Fr::zero(),
+        }
+    }
+}
+
+/// Consecutive rows of a single region, starting at `offset`.
+///
+/// The instance column is added by the gate in every row, so the rows with non-zero instances have to be handled by the
+/// caller (no row assigned here touches the instance column).
+pub(crate) struct Rows<'a, 'r> {
+    pub(crate) region: &'a mut Region<'r, Fr>,
+    pub(crate) config: &'a StandardPlonkConfig<Fr>,
+    pub(crate) offset: usize,
+}
+
+impl Rows<'_, '_> {
+    /// Assign the next row.
+    pub(crate) fn assign(
+        &mut self,
+        inputs: [Input; 3],
+        coefficients: Coefficients,
+    ) -> Result<[Number; 3], Error> {
+        let offset = self.offset;
+        self.offset += 1;
+
+        let columns = [self.config.a, self.config.b, self.config.c];
+        let mut cells = Vec::with_capacity(3);
+        for (input, column) in inputs.into_iter().zip(columns) {
+            let cell = match input {
+                Input::Fresh(value) => {
+                    self.region.assign_advice(|| "", column, offset, || value)?
+                }
+                Input::Copy(number) => number.copy_advice(|| "", self.region, column, offset)?,
+            };
+            cells.push(cell);
+        }
+
+        let fixed = [
+            (self.config.q_a, coefficients.a),
+            (self.config.q_b, coefficients.b),
+            (self.config.q_c, coefficients.c),
+            (self.config.q_ab, coefficients.ab),
+            (self.config.constant, coefficients.constant),
+        ];
+        for (column, value) in fixed.into_iter().filter(|(_, value)| *value != Fr::zero()) {
+            self.region
+                .assign_fixed(|| "", column, offset, || Value::known(value))?;
+        }
+
+        Ok(cells.try_into().unwrap())
+    }
+
+    /// Assign an unconstrained witness.
+    pub(crate) fn witness(&mut self, value: Value<Fr>) -> Result<Number, Error> {
+        let [number, _, _] = self.assign(
+            [Input::Fresh(value), zero(), zero()],
+            Coefficients::default(),
+        )?;
+        Ok(number)
+    }
+
+    /// Assign the constant `value`.
+    pub(crate) fn constant(&mut self, value: Fr) -> Result<Number, Error> {
+        let [number, _, _] = self.assign(
+            [Input::Fresh(Value::known(value)), zero(), zero()],
+            Coefficients {
+                a: Fr::one(),
+                constant: -value,
+                ..Default::default()
+            },
+        )?;
+        Ok(number)
+    }
+
+    /// Compute `x_coefficient * x + y_coefficient * y + constant`.
+    pub(crate) fn linear(
+        &mut self,
+        (x, x_coefficient): (&Number, Fr),
+        (y, y_coefficient): (&Number, Fr),
+        constant: Fr,
+    ) -> Result<Number, Error> {
+        let value = x
+            .value()
+            .zip(y.value())
+            .map(|(x, y)| x_coefficient * x + y_coefficient * y + constant);
+        let [_, _, result] = self.assign(
+            [Input::Copy(x), Input::Copy(y), Input::Fresh(value)],
+            Coefficients {
+                a: x_coefficient,
+                b: y_coefficient,
+                c: -Fr::one(),
+                constant,
+                ..Default::default()
+            },
+        )?;
+        Ok(result)
+    }
+
+    /// Compute `x * y`.
+    pub(crate) fn mul(&mut self, x: &Number, y: &Number) -> Result<Number, Error> {
+        let value = x.value().zip(y.value()).map(|(x, y)| *x * y);
+        let [_, _, result] = self.assign(
+            [Input::Copy(x), Input::Copy(y), Input::Fresh(value)],
+            Coefficients {
+                c: -Fr::one(),
+                ab: Fr::one(),
+                ..Default::default()
+            },
+        )?;
+        Ok(result)
+    }
+
+    /// Check that `x < 2^bits` (for `bits <= 128`). Returns the bits of `x`, starting from the most significant one.
+    pub(crate) fn range_check(&mut self, bits: usize, x: &Number) -> Result<Vec<Number>, Error> {
+        let value = x.value().map(|x| to_u128(*x));
+        let mut accumulator = self.witness(Value::known(Fr::zero()))?;
+        let mut decomposition = Vec::with_capacity(bits);
+        for position in (0..bits).rev() {
+            let bit = value.map(|value| Fr::from(((value >> position) & 1) as u64));
+            // `accumulator' = 2 * accumulator + bit` (the initial accumulator is ignored).
+            let [_, bit, next] = self.assign(
+                [
+                    Input::Copy(&accumulator),
+                    Input::Fresh(bit),
+                    Input::Fresh(accumulator.value().zip(bit).map(|(a, b)| a.double() + b)),
+                ],
+                Coefficients {
+                    a: fr(if decomposition.is_empty() { 0 } else { 2 }),
+                    b: Fr::one(),
+                    c: -Fr::one(),
+                    ..Default::default()
+                },
+            )?;
+            self.assert_boolean(&bit)?;
+            decomposition.push(bit);
+            accumulator = next;
+        }
+        self.region.constrain_equal(accumulator.cell(), x.cell())?;
+        Ok(decomposition)
+    }
+
+    /// Check that `x` is either `0` or `1`.
+    pub(crate) fn assert_boolean(&mut self, x: &Number) -> Result<(), Error> {
+        self.assign(
+            [Input::Copy(x), Input::Copy(x), zero()],
+            Coefficients {
+                a: -Fr::one(),
+                ab: Fr::one(),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// Embed a (possibly negative) integer into the field.
+pub(crate) fn fr(value: i64) -> Fr {
+    let magnitude = Fr::from(value.unsigned_abs());
+    if value < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Lowest 128 bits of `element` (the whole value, as long as it is small enough).
+pub(crate) fn to_u128(element: Fr) -> u128 {
+    u128::from_le_bytes(element.to_repr().as_ref()[..16].try_into().unwrap())
+}
+
+/// Fresh witness equal to zero (for the unused cells of a row).
+pub(crate) fn zero<'n>() -> Input<'n> {
+    Input::Fresh(Value::known(Fr::zero()))
+}
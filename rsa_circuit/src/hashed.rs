@@ -0,0 +1,109 @@
+//! Hashed public input mode of the RSA challenge.
+//!
+//! In [`RsaChallenge`], every context element (the halves of the account, the contract address and the chain id) is a
+//! separate instance and costs a row of the circuit. Here, they are private inputs instead, absorbed in-circuit into
+//! a Poseidon hash, which is the only instance apart from `n` (see [`rsa_public_input::encode_hashed`]). The verifier
+//! (in particular the contract) recomputes the hash with the `no_std` implementation from
+//! [`rsa_public_input::poseidon`], so binding a proof to more context only changes the hashed elements.
+//!
+//! The price is the size of the circuit: the hash takes almost two thousand rows, so proving takes noticeably longer
+//! than for the raw mode. The relation between `p`, `q` and `n` is exactly the same as in [`RsaChallenge`].
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::{Circuit, ConstraintSystem, Error},
+    standard_plonk::StandardPlonk,
+};
+use rsa_public_input::{CONTEXT_ELEMENT_COUNT, HASHED_INSTANCE_COUNT};
+
+use crate::{
+    gadgets::Rows,
+    poseidon,
+    utils::{Account, Domain},
+    RsaChallenge,
+};
+
+/// Logarithm of the number of rows in the PLONK table used for the hashed mode.
+pub const HASHED_CIRCUIT_K: u32 = 12;
+
+/// First row available for the hash (the previous ones are used by the factorization checks).
+const HASH_OFFSET: usize = 5;
+
+/// Circuit representing the RSA challenge in the hashed mode.
+///
+/// There are two instances (public inputs): `n` and the Poseidon hash of the context (account and domain). The advices
+/// (private inputs) are the factors and the context elements.
+#[derive(Default)]
+pub struct HashedRsaChallenge {
+    challenge: RsaChallenge,
+    context: Value<[Fr; CONTEXT_ELEMENT_COUNT]>,
+}
+
+impl HashedRsaChallenge {
+    /// Create a circuit with witnesses for the factorization `p * q` bound to `account` and `domain`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the factors is `0` or `1`.
+    pub fn new(p: u128, q: u128, account: Account, domain: Domain) -> Self {
+        let context = rsa_public_input::encode_context(&account, &domain)
+            .map(|element| Fr::from_repr(element).unwrap());
+        Self {
+            challenge: RsaChallenge::new(p, q),
+            context: Value::known(context),
+        }
+    }
+}
+
+impl Circuit<Fr> for HashedRsaChallenge {
+    type Config = <StandardPlonk as Circuit<Fr>>::Config;
+    type FloorPlanner = <StandardPlonk as Circuit<Fr>>::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        StandardPlonk::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "",
+            |mut region| {
+                self.challenge.assign_factorization(&mut region, &config)?;
+
+                let mut rows = Rows {
+                    region: &mut region,
+                    config: &config,
+                    offset: HASH_OFFSET,
+                };
+                let context = (0..CONTEXT_ELEMENT_COUNT)
+                    .map(|index| rows.witness(self.context.map(|context| context[index])))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let hash = poseidon::hash(&mut rows, &context)?;
+
+                // Bind the hash to the second instance: `-hash + instance = 0`.
+                hash.copy_advice(|| "context hash", &mut region, config.a, 1)?;
+                RsaChallenge::negate_at_selector(&mut region, config.q_a, || "context hash", 1)?;
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Convert the public input of the hashed mode from human-readable form to a scalar array.
+pub fn prepare_public_input(
+    n: u128,
+    account: Account,
+    domain: Domain,
+) -> [Fr; HASHED_INSTANCE_COUNT] {
+    rsa_public_input::encode_hashed(n, &account, &domain)
+        .map(|element| Fr::from_repr(element).unwrap())
+}
@@ -0,0 +1,281 @@
+//! Cost report of the circuits: how much of the PLONK table they use and how big their proofs are.
+//!
+//! The table usage is measured by synthesizing the circuit without witnesses, so that only the placement of the cells
+//! is recorded. The proof size is computed from the constraint system for the GWC multi-open scheme (the one expected
+//! by the on-chain verifier). With the `dev-graph` feature, the layout of the circuit can also be rendered to a file
+//! (see [`render_layout`]).
+
+use std::collections::BTreeSet;
+#[cfg(feature = "dev-graph")]
+use std::{io, path::Path};
+
+#[cfg(feature = "dev-graph")]
+use halo2_proofs::dev::CircuitLayout;
+use halo2_proofs::{
+    circuit::Value,
+    halo2curves::bn256::Fr,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, FloorPlanner, Instance, Selector,
+    },
+};
+#[cfg(feature = "dev-graph")]
+use plotters::{
+    coord::Shift,
+    drawing::{DrawingArea, DrawingAreaErrorKind, IntoDrawingArea},
+    prelude::{BitMapBackend, DrawingBackend, SVGBackend, WHITE},
+};
+use rsa_public_input::FIELD_ELEMENT_SIZE;
+
+/// Size of a (compressed) commitment in the proof.
+const POINT_SIZE: usize = 32;
+
+/// Number of evaluations in the proof for every lookup argument.
+const LOOKUP_EVALUATIONS: usize = 5;
+
+/// Size of the layout rendered by [`render_layout`] (in pixels).
+#[cfg(feature = "dev-graph")]
+const LAYOUT_SIZE: (u32, u32) = (1024, 768);
+
+/// Cost of a circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitInfo {
+    /// Logarithm of the number of rows in the PLONK table that the circuit is used with.
+    pub k: u32,
+    /// Number of rows used by the circuit (up to the last assigned one).
+    pub rows: usize,
+    /// Number of rows available for the circuit in the table of size `2^k` (the rest is reserved by halo2 for
+    /// blinding).
+    pub usable_rows: usize,
+    /// Number of advice columns.
+    pub advice_columns: usize,
+    /// Number of fixed columns.
+    pub fixed_columns: usize,
+    /// Number of instance columns.
+    pub instance_columns: usize,
+    /// Maximum degree of the constraints (including the permutation argument).
+    pub max_degree: usize,
+    /// Number of instances (public inputs).
+    pub instances: usize,
+    /// Logarithm of the smallest table that fits the circuit.
+    pub min_k: u32,
+    /// Size of a proof (in bytes), with the GWC multi-open scheme.
+    pub proof_size: usize,
+}
+
+/// Measure the cost of `circuit` used with the table of size `2^k`.
+pub fn measure<C: Circuit<Fr>>(k: u32, circuit: &C) -> CircuitInfo {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+
+    let mut usage = Usage::default();
+    C::FloorPlanner::synthesize(&mut usage, circuit, config, cs.constants().clone())
+        .expect("Synthesis without witnesses should not fail");
+
+    let reserved_rows = cs.blinding_factors() + 1;
+    let required_rows = (usage.rows.max(usage.instances) + reserved_rows).max(cs.minimum_rows());
+
+    CircuitInfo {
+        k,
+        rows: usage.rows,
+        usable_rows: (1usize << k).saturating_sub(reserved_rows),
+        advice_columns: cs.num_advice_columns(),
+        fixed_columns: cs.num_fixed_columns(),
+        instance_columns: cs.num_instance_columns(),
+        max_degree: cs.degree(),
+        instances: usage.instances,
+        min_k: required_rows.next_power_of_two().trailing_zeros(),
+        proof_size: proof_size(&cs),
+    }
+}
+
+/// Compute the size of a proof for the constraint system `cs` with the GWC multi-open scheme, following the order in
+/// which the prover writes to the transcript. The instances are not part of the proof.
+fn proof_size(cs: &ConstraintSystem<Fr>) -> usize {
+    let permutation_columns = cs.permutation().get_columns().len();
+    let chunk_len = cs.degree() - 2;
+    let permutation_chunks = (permutation_columns + chunk_len - 1) / chunk_len;
+    let lookups = cs.lookups().len();
+
+    // Rotations at which the polynomials are opened: GWC commits to a single witness for each of them.
+    let mut rotations = BTreeSet::from([0]);
+    rotations.extend(cs.advice_queries().iter().map(|(_, rotation)| rotation.0));
+    rotations.extend(cs.fixed_queries().iter().map(|(_, rotation)| rotation.0));
+    if permutation_chunks > 0 {
+        rotations.insert(1);
+    }
+    if permutation_chunks > 1 {
+        rotations.insert(-((cs.blinding_factors() + 1) as i32));
+    }
+    if lookups > 0 {
+        rotations.extend([1, -1]);
+    }
+
+    let commitments = cs.num_advice_columns()
+        // permuted input and table, and the product
+        + 3 * lookups
+        + permutation_chunks
+        // random polynomial and the pieces of the quotient
+        + cs.degree()
+        + rotations.len();
+    let evaluations = cs.advice_queries().len()
+        + cs.fixed_queries().len()
+        // random polynomial
+        + 1
+        + permutation_columns
+        // every product at `x` and `ωx`, and all but the last one at the last usable row
+        + (3 * permutation_chunks).saturating_sub(1)
+        + LOOKUP_EVALUATIONS * lookups;
+
+    commitments * POINT_SIZE + evaluations * FIELD_ELEMENT_SIZE
+}
+
+/// Render the layout of `circuit` used with the table of size `2^k` to the file at `path`: SVG if its extension is
+/// `svg` and PNG otherwise. Equality constraints are shown, so that the copies between the rows can be followed.
+#[cfg(feature = "dev-graph")]
+pub fn render_layout<C: Circuit<Fr>>(k: u32, circuit: &C, path: &Path) -> io::Result<()> {
+    match path.extension() {
+        Some(extension) if extension == "svg" => draw(
+            SVGBackend::new(path, LAYOUT_SIZE).into_drawing_area(),
+            k,
+            circuit,
+        ),
+        _ => draw(
+            BitMapBackend::new(path, LAYOUT_SIZE).into_drawing_area(),
+            k,
+            circuit,
+        ),
+    }
+}
+
+#[cfg(feature = "dev-graph")]
+fn draw<DB: DrawingBackend, C: Circuit<Fr>>(
+    area: DrawingArea<DB, Shift>,
+    k: u32,
+    circuit: &C,
+) -> io::Result<()> {
+    let to_io_error = |error: DrawingAreaErrorKind<DB::ErrorType>| {
+        io::Error::new(io::ErrorKind::Other, error.to_string())
+    };
+
+    area.fill(&WHITE).map_err(to_io_error)?;
+    CircuitLayout::default()
+        .mark_equality_cells(true)
+        .show_equality_constraints(true)
+        .render(k, circuit, &area)
+        .map_err(to_io_error)?;
+    area.present().map_err(to_io_error)
+}
+
+/// Rows and instances referenced during the synthesis. The values themselves are never computed.
+#[derive(Default)]
+struct Usage {
+    rows: usize,
+    instances: usize,
+}
+
+impl Usage {
+    fn use_row(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+
+    fn use_cell(&mut self, column: Column<Any>, row: usize) {
+        match column.column_type() {
+            Any::Instance => self.instances = self.instances.max(row + 1),
+            _ => self.use_row(row),
+        }
+    }
+}
+
+impl Assignment<Fr> for Usage {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<Fr>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    // Instances are only ever referenced by copying them into advice cells.
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.use_cell(left_column, left_row);
+        self.use_cell(right_column, right_row);
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<Fr>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<Fr> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
@@ -3,24 +3,44 @@
 #![deny(missing_docs)]
 
 use halo2_proofs::{
+    arithmetic::Field,
     circuit::{Cell, Layouter, Region, Value},
-    halo2curves::bn256::Fr,
-    plonk::{Circuit, Column, ConstraintSystem, Error, Fixed},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed},
     standard_plonk::{StandardPlonk, StandardPlonkConfig},
 };
 
+pub mod backend;
+pub mod batch;
+pub mod challenge;
+mod gadgets;
+pub mod hashed;
+pub mod info;
+pub mod multi_factor;
+mod poseidon;
+pub mod preimage;
+pub mod private_key;
 #[cfg(test)]
 mod tests;
+pub mod transcript;
 pub mod utils;
+pub mod well_formedness;
+
+/// Logarithm of the number of rows in the PLONK table used for the RSA challenge (the smallest one that fits the circuit
+/// together with the rows reserved by halo2 for blinding).
+pub const CIRCUIT_MAX_K: u32 = 5;
 
 /// Circuit representing the RSA challenge.
 ///
-/// There are two advices (private inputs): `p`, and `q` and two instances (public inputs): `n` and `account`. The
-/// values should satisfy: `p * q = n`. The `account` instance is artificially included in the circuit to prevent
-/// front running attacks.
+/// There are two advices (private inputs): `p`, and `q` and three instances (public inputs): `n`, `account` and
+/// `domain`. The values should satisfy: `p * q = n`. The `account` instance is artificially included in the circuit to
+/// prevent front running attacks. Likewise, the `domain` (contract address and chain id) binds the proof to a single
+/// deployment of the challenge, so that it cannot be replayed against another one.
 ///
-/// Since `account` is usually 256 bits long (`[u8; 32]`), we need to split it into two 128 bit chunks, so that we have
-/// certainty that both can be safely decoded as `Fr` elements.
+/// Since `account`, contract address and chain id are usually 256 bits long (`[u8; 32]`), we need to split each of them
+/// into two 128 bit chunks, so that we have certainty that all of them can be safely decoded as `Fr` elements. Shorter
+/// accounts (e.g. 20-byte EVM addresses) are laid out in the same two instances, together with their length (see
+/// [`rsa_public_input::Account::encode`]), so the circuit itself doesn't depend on the account format.
 ///
 /// # Exploit
 ///
@@ -61,45 +81,13 @@ impl Circuit<Fr> for RsaChallenge {
         layouter.assign_region(
             || "",
             |mut region| {
-                let p_value = || Value::known(self.p.unwrap());
-                let p_dec_inv_value = || Value::known(self.p_dec_inv.unwrap());
-                let q_value = || Value::known(self.q.unwrap());
-                let q_dec_inv_value = || Value::known(self.q_dec_inv.unwrap());
-
-                // Check that `p*q = n`.
-                let p_cell = region.assign_advice(|| "p", config.a, 0, p_value)?;
-                let q_cell = region.assign_advice(|| "q", config.b, 0, q_value)?;
-                Self::negate_at_selector(&mut region, config.q_ab, || "p*q", 0)?;
+                self.assign_factorization(&mut region, &config)?;
 
                 // Zero out the rest of the instances just by negating them. This way we are ensuring that they will be
-                // embedded into the proof (committed to).
-                let ann1 = || "account low";
-                region.assign_advice_from_instance(ann1, config.instance, 1, config.a, 1)?;
-                Self::negate_at_selector(&mut region, config.q_a, ann1, 1)?;
-
-                let ann2 = || "account_high";
-                region.assign_advice_from_instance(ann2, config.instance, 2, config.a, 2)?;
-                Self::negate_at_selector(&mut region, config.q_a, ann2, 2)?;
-
-                // Check that both `p` and `q` are greater than 1.
-                Self::check_non_triviality(
-                    &mut region,
-                    'p',
-                    p_value,
-                    p_dec_inv_value,
-                    3,
-                    p_cell.cell(),
-                    &config,
-                )?;
-                Self::check_non_triviality(
-                    &mut region,
-                    'q',
-                    q_value,
-                    q_dec_inv_value,
-                    4,
-                    q_cell.cell(),
-                    &config,
-                )?;
+                // embedded into the proof (committed to). The `a` column of rows 3 and 4 is taken by the non-triviality
+                // checks, so the contract halves go to the (still free) `c` column there.
+                let (a, c) = ((config.a, config.q_a), (config.c, config.q_c));
+                Self::bind_context(&mut region, &config, 1, [a, a, c, c, a, a])?;
 
                 Ok(())
             },
@@ -108,6 +96,63 @@ impl Circuit<Fr> for RsaChallenge {
 }
 
 impl RsaChallenge {
+    /// Create a circuit with witnesses for the factorization `p * q`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the factors is `0` or `1`.
+    pub fn new(p: u128, q: u128) -> Self {
+        // `p - 1` would wrap around for `0` in release builds, leaving an invertible, but unsatisfiable, witness.
+        assert!(p > 1 && q > 1, "Factors must be greater than 1");
+        Self {
+            p: Some(Fr::from_u128(p)),
+            p_dec_inv: Some(Fr::from_u128(p - 1).invert().unwrap()),
+            q: Some(Fr::from_u128(q)),
+            q_dec_inv: Some(Fr::from_u128(q - 1).invert().unwrap()),
+        }
+    }
+
+    /// Assign the rows checking that `p * q = n` (row 0) and that both factors are non-trivial (rows 3 and 4).
+    ///
+    /// Rows 1 and 2 are left for binding other instances (the `c` column of rows 3 and 4 is also free).
+    fn assign_factorization(
+        &self,
+        region: &mut Region<Fr>,
+        config: &StandardPlonkConfig<Fr>,
+    ) -> Result<(), Error> {
+        let p_value = || Value::known(self.p.unwrap());
+        let p_dec_inv_value = || Value::known(self.p_dec_inv.unwrap());
+        let q_value = || Value::known(self.q.unwrap());
+        let q_dec_inv_value = || Value::known(self.q_dec_inv.unwrap());
+
+        // Check that `p*q = n`.
+        let p_cell = region.assign_advice(|| "p", config.a, 0, p_value)?;
+        let q_cell = region.assign_advice(|| "q", config.b, 0, q_value)?;
+        Self::negate_at_selector(region, config.q_ab, || "p*q", 0)?;
+
+        // Check that both `p` and `q` are greater than 1.
+        Self::check_non_triviality(
+            region,
+            'p',
+            p_value,
+            p_dec_inv_value,
+            3,
+            p_cell.cell(),
+            config,
+        )?;
+        Self::check_non_triviality(
+            region,
+            'q',
+            q_value,
+            q_dec_inv_value,
+            4,
+            q_cell.cell(),
+            config,
+        )?;
+
+        Ok(())
+    }
+
     fn negate_at_selector(
         region: &mut Region<Fr>,
         selector: Column<Fixed>,
@@ -120,6 +165,44 @@ impl RsaChallenge {
         Ok(())
     }
 
+    /// Copy the instance at `row` to `column` in the same row and zero it out by negation (with `selector`).
+    fn bind_instance(
+        region: &mut Region<Fr>,
+        config: &StandardPlonkConfig<Fr>,
+        annotation: impl Fn() -> &'static str + Copy,
+        row: usize,
+        column: Column<Advice>,
+        selector: Column<Fixed>,
+    ) -> Result<(), Error> {
+        region.assign_advice_from_instance(annotation, config.instance, row, column, row)?;
+        Self::negate_at_selector(region, selector, annotation, row)
+    }
+
+    /// Bind the context (the halves of the account, the contract address and the chain id) given by the six instances
+    /// starting at `first_row`. Each of them is copied to its advice column from `columns` and zeroed out (with the
+    /// paired selector) in the row with its own index.
+    fn bind_context(
+        region: &mut Region<Fr>,
+        config: &StandardPlonkConfig<Fr>,
+        first_row: usize,
+        columns: [(Column<Advice>, Column<Fixed>); 6],
+    ) -> Result<(), Error> {
+        let annotations = [
+            "account low",
+            "account high",
+            "contract low",
+            "contract high",
+            "chain id low",
+            "chain id high",
+        ];
+        for (row, (annotation, (column, selector))) in
+            (first_row..).zip(annotations.into_iter().zip(columns))
+        {
+            Self::bind_instance(region, config, || annotation, row, column, selector)?;
+        }
+        Ok(())
+    }
+
     fn check_non_triviality(
         region: &mut Region<Fr>,
         // either 'p' or 'q'
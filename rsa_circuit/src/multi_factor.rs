@@ -0,0 +1,154 @@
+//! Generalization of the RSA challenge to any number of factors, e.g. for multi-prime RSA bounties or challenges to
+//! fully factorize a number.
+//!
+//! The circuit proves the knowledge of `k` factors, each greater than 1, whose product is `n`. The public input is the
+//! same as for [`RsaChallenge`](crate::RsaChallenge) (see [`utils::prepare_public_input`](crate::utils::prepare_public_input)), so such a challenge can be
+//! deployed with the same contract, just under the verifying key of this circuit. The number of factors is a parameter
+//! of the circuit (different `k`s lead to different verifying keys), between 2 and [`MAX_FACTOR_COUNT`].
+//!
+//! The factors must be given in non-increasing order, which makes the witness canonical and bounds all but the first
+//! factor by `sqrt(n) < 2^64`. Together with range checks of the partial products, this guarantees that no
+//! multiplication wraps around the field modulus, i.e. that the relation holds over the integers.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, Value},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::{Circuit, ConstraintSystem, Error},
+    standard_plonk::StandardPlonk,
+};
+
+use crate::{
+    gadgets::{fr, Rows},
+    RsaChallenge,
+};
+
+/// Logarithm of the number of rows in the PLONK table used for the multi-factor circuit (enough for
+/// [`MAX_FACTOR_COUNT`] factors).
+pub const MULTI_FACTOR_K: u32 = 13;
+
+/// Maximal number of factors.
+pub const MAX_FACTOR_COUNT: usize = 8;
+
+/// Number of bits of `n` and of all the partial products.
+const PRODUCT_BITS: usize = 128;
+
+/// Number of bits of all the factors but the first (the largest) one.
+const SMALL_FACTOR_BITS: usize = 64;
+
+/// First row available for the factors (the previous ones are used for the instances).
+const FACTORS_OFFSET: usize = 7;
+
+/// Circuit representing a challenge to factorize `n` into a fixed number of non-trivial factors.
+///
+/// There are seven instances, exactly as in [`RsaChallenge`]: `n` and the halves of the account, the contract address
+/// and the chain id. The advices are the factors.
+pub struct MultiFactorChallenge {
+    pub(crate) factors: Vec<Value<Fr>>,
+}
+
+impl MultiFactorChallenge {
+    /// Create a circuit with witnesses for the factorization of `n = factors[0] * factors[1] * ...` (the factors are
+    /// sorted, so their order doesn't matter).
+    ///
+    /// Returns `None` if the number of factors is not between 2 and [`MAX_FACTOR_COUNT`], any of them is smaller than
+    /// 2 or their product doesn't fit into `u128`.
+    pub fn new(factors: &[u128]) -> Option<Self> {
+        product(factors)?;
+        let mut factors = factors.to_vec();
+        factors.sort_unstable_by(|a, b| b.cmp(a));
+        Some(Self {
+            factors: factors
+                .into_iter()
+                .map(|factor| Value::known(Fr::from_u128(factor)))
+                .collect(),
+        })
+    }
+
+    /// Create a circuit for `factor_count` factors without witnesses (e.g. for key generation).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor_count` is not between 2 and [`MAX_FACTOR_COUNT`].
+    pub fn without_factors(factor_count: usize) -> Self {
+        assert!(
+            (2..=MAX_FACTOR_COUNT).contains(&factor_count),
+            "Unsupported number of factors: {factor_count}"
+        );
+        Self {
+            factors: vec![Value::unknown(); factor_count],
+        }
+    }
+}
+
+impl Circuit<Fr> for MultiFactorChallenge {
+    type Config = <StandardPlonk as Circuit<Fr>>::Config;
+    type FloorPlanner = <StandardPlonk as Circuit<Fr>>::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::without_factors(self.factors.len())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        StandardPlonk::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "",
+            |mut region| {
+                let mut rows = Rows {
+                    region: &mut region,
+                    config: &config,
+                    offset: FACTORS_OFFSET,
+                };
+
+                // The first factor is the largest one, so it is bounded only by `n`.
+                let mut previous = rows.witness(self.factors[0])?;
+                rows.range_check(PRODUCT_BITS, &previous)?;
+                let mut product = previous.clone();
+
+                for factor in &self.factors[1..] {
+                    let factor = rows.witness(*factor)?;
+                    rows.range_check(SMALL_FACTOR_BITS, &factor)?;
+                    // `previous >= factor`
+                    let difference =
+                        rows.linear((&previous, Fr::one()), (&factor, -Fr::one()), Fr::zero())?;
+                    rows.range_check(PRODUCT_BITS, &difference)?;
+
+                    // Both multiplicands are small enough not to wrap around, and so is the result.
+                    product = rows.mul(&product, &factor)?;
+                    rows.range_check(PRODUCT_BITS, &product)?;
+                    previous = factor;
+                }
+
+                // The last factor is the smallest one, so all of them are greater than 1: `previous - 2 >= 0`.
+                let above_one =
+                    rows.linear((&previous, Fr::one()), (&previous, Fr::zero()), -fr(2))?;
+                rows.range_check(SMALL_FACTOR_BITS, &above_one)?;
+
+                // Bind the product to `n` and the rest of the instances (see `RsaChallenge::bind_context`).
+                product.copy_advice(|| "n", &mut region, config.a, 0)?;
+                RsaChallenge::negate_at_selector(&mut region, config.q_a, || "n", 0)?;
+                RsaChallenge::bind_context(&mut region, &config, 1, [(config.a, config.q_a); 6])?;
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Product of `factors`, if they are valid (see [`MultiFactorChallenge::new`]).
+pub(crate) fn product(factors: &[u128]) -> Option<u128> {
+    if !(2..=MAX_FACTOR_COUNT).contains(&factors.len()) || factors.iter().any(|factor| *factor < 2)
+    {
+        return None;
+    }
+    factors
+        .iter()
+        .try_fold(1u128, |product, factor| product.checked_mul(*factor))
+}
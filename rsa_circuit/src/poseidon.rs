@@ -0,0 +1,132 @@
+//! In-circuit counterpart of [`rsa_public_input::poseidon`].
+//!
+//! The permutation is evaluated with the standard PLONK gate only (see [`crate::gadgets`]), so it is not particularly
+//! cheap (roughly 630 rows per permutation), but it can be used by any circuit whose keys are read as
+//! [`StandardPlonk`](halo2_proofs::standard_plonk::StandardPlonk) ones.
+//!
+//! Round constants are not added in separate rows. Every state element is kept together with a pending constant
+//! (`value = number + offset`), which is folded into the next S-box or MDS row.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::Error,
+};
+use rsa_public_input::{
+    field::Scalar,
+    poseidon::{FULL_ROUNDS, MDS, PARTIAL_ROUNDS, RATE, ROUND_CONSTANTS, WIDTH},
+};
+
+use crate::gadgets::{Coefficients, Input, Number, Rows};
+
+/// State element with a pending constant: its value is `number + offset`.
+type Element = (Number, Fr);
+
+/// Compute [`rsa_public_input::poseidon::hash`] of `elements`.
+pub(crate) fn hash(rows: &mut Rows, elements: &[Number]) -> Result<Number, Error> {
+    let capacity = Fr::from_u128((elements.len() as u128) << 64);
+    let mut state = [
+        rows.constant(capacity)?,
+        rows.constant(Fr::zero())?,
+        rows.constant(Fr::zero())?,
+    ];
+
+    for chunk in elements.chunks(RATE) {
+        for (offset, element) in chunk.iter().enumerate() {
+            state[1 + offset] = rows.linear(
+                (&state[1 + offset], Fr::one()),
+                (element, Fr::one()),
+                Fr::zero(),
+            )?;
+        }
+        state = permute(rows, state)?;
+    }
+    // The sponge permutes before absorbing into a full state and before squeezing, i.e. once per chunk (and once even
+    // if nothing was absorbed).
+    if elements.is_empty() {
+        state = permute(rows, state)?;
+    }
+
+    let [_, output, _] = state;
+    Ok(output)
+}
+
+/// Apply the Poseidon permutation to `state`.
+fn permute(rows: &mut Rows, state: [Number; WIDTH]) -> Result<[Number; WIDTH], Error> {
+    let mut state = state.map(|number| (number, Fr::zero()));
+
+    for (round, constants) in ROUND_CONSTANTS.iter().enumerate() {
+        let partial_rounds = FULL_ROUNDS / 2..FULL_ROUNDS / 2 + PARTIAL_ROUNDS;
+        let mut substituted = Vec::with_capacity(WIDTH);
+        for (index, ((number, offset), constant)) in state.into_iter().zip(constants).enumerate() {
+            let offset = offset + to_fr(constant);
+            if index == 0 || !partial_rounds.contains(&round) {
+                substituted.push((sbox(rows, &number, offset)?, Fr::zero()));
+            } else {
+                substituted.push((number, offset));
+            }
+        }
+
+        let mut mixed = Vec::with_capacity(WIDTH);
+        for row in MDS {
+            mixed.push((mix(rows, &substituted, row.map(|c| to_fr(&c)))?, Fr::zero()));
+        }
+        state = mixed.try_into().unwrap_or_else(|_| unreachable!());
+    }
+
+    Ok(state.map(|(number, _)| number))
+}
+
+/// Compute `(x + offset)^5` in three rows.
+fn sbox(rows: &mut Rows, x: &Number, offset: Fr) -> Result<Number, Error> {
+    let shifted = x.value().map(|x| *x + offset);
+
+    // `x2 = x*x + 2*offset*x + offset^2`
+    let [_, _, x2] = rows.assign(
+        [
+            Input::Copy(x),
+            Input::Copy(x),
+            Input::Fresh(shifted.map(|x| x.square())),
+        ],
+        Coefficients {
+            a: offset.double(),
+            c: -Fr::one(),
+            ab: Fr::one(),
+            constant: offset.square(),
+            ..Default::default()
+        },
+    )?;
+    let x4 = rows.mul(&x2, &x2)?;
+    // `x5 = x4*x + offset*x4`
+    let [_, _, x5] = rows.assign(
+        [
+            Input::Copy(&x4),
+            Input::Copy(x),
+            Input::Fresh(x4.value().zip(shifted).map(|(x4, x)| *x4 * x)),
+        ],
+        Coefficients {
+            a: offset,
+            c: -Fr::one(),
+            ab: Fr::one(),
+            ..Default::default()
+        },
+    )?;
+    Ok(x5)
+}
+
+/// Compute the inner product of `row` and `state` in two rows.
+fn mix(rows: &mut Rows, state: &[Element], row: [Fr; WIDTH]) -> Result<Number, Error> {
+    let [(x, x_offset), (y, y_offset), (z, z_offset)] = state else {
+        unreachable!("State has exactly `WIDTH` elements");
+    };
+    let partial = rows.linear(
+        (x, row[0]),
+        (y, row[1]),
+        row[0] * x_offset + row[1] * y_offset,
+    )?;
+    rows.linear((&partial, Fr::one()), (z, row[2]), row[2] * z_offset)
+}
+
+fn to_fr(scalar: &Scalar) -> Fr {
+    Fr::from_repr(scalar.to_bytes()).unwrap()
+}
@@ -0,0 +1,104 @@
+//! Hash-preimage bounty: the solver proves the knowledge of `x` with `Poseidon(x) = h` for the published digest `h`,
+//! without revealing it.
+//!
+//! The hash is [`rsa_public_input::poseidon::hash`] of the single element `x` (see [`rsa_public_input::hash_preimage`]),
+//! computed in-circuit with the same gadget as in the [`hashed`](crate::hashed) mode. Both `x` and `h` are passed
+//! around as field elements in little-endian bytes. The public input is the digest followed by the context (see
+//! [`rsa_public_input::encode_preimage`]), so the proof is bound to the participant and the deployment, just like for
+//! [`RsaChallenge`].
+//!
+//! SHA-256 digests are not supported: with the standard PLONK gate only, the compression function would take tens of
+//! thousands of rows, far beyond the table sizes used by the other challenges.
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::{Circuit, ConstraintSystem, Error},
+    standard_plonk::StandardPlonk,
+};
+use rsa_public_input::PREIMAGE_INSTANCE_COUNT;
+pub use rsa_public_input::{hash_preimage, is_canonical, FieldElementBytes};
+
+use crate::{
+    gadgets::Rows,
+    poseidon,
+    utils::{Account, Domain},
+    RsaChallenge,
+};
+
+/// Logarithm of the number of rows in the PLONK table used for the hash-preimage circuit.
+pub const PREIMAGE_K: u32 = 10;
+
+/// First row available for the hash (the previous ones are used for the instances).
+const HASH_OFFSET: usize = PREIMAGE_INSTANCE_COUNT;
+
+/// Circuit representing a challenge to find a preimage of a Poseidon digest.
+///
+/// There are seven instances: the digest and the halves of the account, the contract address and the chain id. The
+/// only advice is the preimage.
+#[derive(Default)]
+pub struct PreimageChallenge {
+    preimage: Value<Fr>,
+}
+
+impl PreimageChallenge {
+    /// Create a circuit with the witness `preimage` (a field element in little-endian bytes). Returns `None` if it is
+    /// not canonical.
+    pub fn new(preimage: &FieldElementBytes) -> Option<Self> {
+        let preimage = Option::<Fr>::from(Fr::from_repr(*preimage))?;
+        Some(Self {
+            preimage: Value::known(preimage),
+        })
+    }
+}
+
+impl Circuit<Fr> for PreimageChallenge {
+    type Config = <StandardPlonk as Circuit<Fr>>::Config;
+    type FloorPlanner = <StandardPlonk as Circuit<Fr>>::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        StandardPlonk::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "",
+            |mut region| {
+                let mut rows = Rows {
+                    region: &mut region,
+                    config: &config,
+                    offset: HASH_OFFSET,
+                };
+                let preimage = rows.witness(self.preimage)?;
+                let hash = poseidon::hash(&mut rows, &[preimage])?;
+
+                // Bind the hash to the digest and the rest of the instances (see `RsaChallenge::bind_context`).
+                hash.copy_advice(|| "hash", &mut region, config.a, 0)?;
+                RsaChallenge::negate_at_selector(&mut region, config.q_a, || "hash", 0)?;
+                RsaChallenge::bind_context(&mut region, &config, 1, [(config.a, config.q_a); 6])?;
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Convert the public input from human-readable form to a scalar array. Returns `None` if `hash` is not canonical.
+pub fn prepare_public_input(
+    hash: &FieldElementBytes,
+    account: Account,
+    domain: Domain,
+) -> Option<[Fr; PREIMAGE_INSTANCE_COUNT]> {
+    Some(
+        rsa_public_input::encode_preimage(hash, &account, &domain)?
+            .map(|element| Fr::from_repr(element).unwrap()),
+    )
+}
@@ -0,0 +1,257 @@
+//! Sibling of the RSA challenge, in which the solver proves the knowledge of a valid private exponent `d` for the
+//! published public key `(n, e)`, without revealing it.
+//!
+//! The circuit proves the knowledge of `p >= q >= 2`, `g` and `d`, such that `n = p * q`, `g` divides both `p - 1`
+//! and `q - 1`, and `e * d = 1 (mod L)`, where `L = (p - 1) * (q - 1) / g`. The exponent is reduced modulo `L`
+//! (`d < L`), which keeps all the values small: no multiplication in the circuit wraps around the field modulus.
+//!
+//! The factors are not checked to be prime, so the soundness relies on `n` being a product of two primes (which the
+//! deployer can prove with the [`well_formedness`](crate::well_formedness) circuit). Then `p` and `q` are these primes
+//! and `L` is a multiple of the Carmichael function `λ(n)` (equal to it for `g = gcd(p - 1, q - 1)`), so `d` is a valid
+//! private exponent. For any other `n`, a proof only shows that `e * d = 1 (mod L)` for some split of `n` into two
+//! factors, which does not make `d` a valid private exponent.
+//!
+//! The public input is `n`, `e` and the context (see [`rsa_public_input::encode_private_key`]), so that the proof is
+//! bound to the participant and the deployment, just like for [`RsaChallenge`].
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, Value},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::{Circuit, ConstraintSystem, Error},
+    standard_plonk::StandardPlonk,
+};
+use rsa_public_input::PRIVATE_KEY_INSTANCE_COUNT;
+
+use crate::{
+    gadgets::{fr, to_u128, zero, Coefficients, Input, Number, Rows},
+    utils::{Account, Domain},
+    RsaChallenge,
+};
+
+/// Logarithm of the number of rows in the PLONK table used for the private-key circuit.
+pub const PRIVATE_KEY_K: u32 = 12;
+
+/// Number of bits of `n` and all the values bounded by it.
+const WIDE_BITS: usize = 128;
+
+/// Number of bits of `e`, `q` (the smaller factor), `g` and all the values bounded by them.
+const NARROW_BITS: usize = 64;
+
+/// First row available for the relation (the previous ones are used for the instances).
+const RELATION_OFFSET: usize = PRIVATE_KEY_INSTANCE_COUNT;
+
+/// RSA public key, the statement of the private-key challenge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicKey {
+    /// Modulus.
+    pub n: u128,
+    /// Public exponent.
+    pub e: u64,
+}
+
+/// Circuit representing a challenge to find a private exponent for the RSA public key `(n, e)`.
+///
+/// There are eight instances: `n`, `e` and the halves of the account, the contract address and the chain id. The
+/// advices are the factors, the common divisor `g` and the private exponent, together with the quotient of
+/// `e * d - 1` and `L`.
+#[derive(Default)]
+pub struct PrivateKeyChallenge {
+    pub(crate) witness: Value<PrivateKeyWitness>,
+}
+
+/// Private inputs of [`PrivateKeyChallenge`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PrivateKeyWitness {
+    /// The larger factor.
+    pub(crate) p: u128,
+    /// The smaller factor.
+    pub(crate) q: u128,
+    /// Common divisor of `p - 1` and `q - 1`.
+    pub(crate) g: u128,
+    /// Private exponent, reduced modulo `L`.
+    pub(crate) d: u128,
+    /// Quotient `(e * d - 1) / L`.
+    pub(crate) t: u128,
+}
+
+impl PrivateKeyChallenge {
+    /// Create a circuit with witnesses for the private exponent `d` of the public key `(p * q, e)`.
+    ///
+    /// Returns `None` if any of the factors is smaller than 2, their product doesn't fit into `u128` or `d` is not a
+    /// valid private exponent, i.e. `e * d != 1 (mod λ(p * q))`.
+    pub fn new(p: u128, q: u128, e: u64, d: u128) -> Option<Self> {
+        let (p, q) = (p.max(q), p.min(q));
+        if q < 2 {
+            return None;
+        }
+        p.checked_mul(q)?;
+
+        let g = gcd(p - 1, q - 1);
+        let lambda = (p - 1) / g * (q - 1);
+        let d = d % lambda;
+        if mul_mod(e as u128 % lambda, d, lambda) != 1 {
+            return None;
+        }
+        // `e * d - 1` is a multiple of `lambda` smaller than `2^192`, so the division is exact also in the field.
+        let t =
+            (Fr::from(e) * Fr::from_u128(d) - Fr::one()) * Fr::from_u128(lambda).invert().unwrap();
+
+        Some(Self {
+            witness: Value::known(PrivateKeyWitness {
+                p,
+                q,
+                g,
+                d,
+                t: to_u128(t),
+            }),
+        })
+    }
+
+    fn value(&self, field: impl Fn(&PrivateKeyWitness) -> u128) -> Value<Fr> {
+        self.witness.map(|witness| Fr::from_u128(field(&witness)))
+    }
+}
+
+impl Circuit<Fr> for PrivateKeyChallenge {
+    type Config = <StandardPlonk as Circuit<Fr>>::Config;
+    type FloorPlanner = <StandardPlonk as Circuit<Fr>>::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        StandardPlonk::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "",
+            |mut region| {
+                // Bind `e`: `-e + instance = 0`.
+                let e =
+                    region.assign_advice_from_instance(|| "e", config.instance, 1, config.a, 1)?;
+                RsaChallenge::negate_at_selector(&mut region, config.q_a, || "e", 1)?;
+
+                let mut rows = Rows {
+                    region: &mut region,
+                    config: &config,
+                    offset: RELATION_OFFSET,
+                };
+                rows.range_check(NARROW_BITS, &e)?;
+
+                // `p >= q >= 2`, so `q <= sqrt(n) < 2^64`.
+                let p = rows.witness(self.value(|witness| witness.p))?;
+                rows.range_check(WIDE_BITS, &p)?;
+                let q = rows.witness(self.value(|witness| witness.q))?;
+                rows.range_check(NARROW_BITS, &q)?;
+                rows.check_at_least(WIDE_BITS, &p, &q, Fr::zero())?;
+                let q_minus_two = rows.linear((&q, Fr::one()), (&q, Fr::zero()), -fr(2))?;
+                rows.range_check(NARROW_BITS, &q_minus_two)?;
+                let n = rows.mul(&p, &q)?;
+
+                // `g` divides both `p - 1 = a * g` and `q - 1 = b * g`.
+                let g = rows.witness(self.value(|witness| witness.g))?;
+                rows.range_check(NARROW_BITS, &g)?;
+                let a = rows.witness(self.value(|witness| (witness.p - 1) / witness.g))?;
+                rows.range_check(WIDE_BITS, &a)?;
+                let b = rows.witness(self.value(|witness| (witness.q - 1) / witness.g))?;
+                rows.range_check(NARROW_BITS, &b)?;
+                let p_minus_one = rows.linear((&p, Fr::one()), (&p, Fr::zero()), -Fr::one())?;
+                let q_minus_one = rows.linear((&q, Fr::one()), (&q, Fr::zero()), -Fr::one())?;
+                let ag = rows.mul(&a, &g)?;
+                rows.region.constrain_equal(ag.cell(), p_minus_one.cell())?;
+                let bg = rows.mul(&b, &g)?;
+                rows.region.constrain_equal(bg.cell(), q_minus_one.cell())?;
+
+                // `L = (p - 1) * (q - 1) / g`
+                let lambda = rows.mul(&a, &q_minus_one)?;
+                rows.range_check(WIDE_BITS, &lambda)?;
+
+                // `e * d = 1 + t * L` with `d < L` (and so `t < e`).
+                let d = rows.witness(self.value(|witness| witness.d))?;
+                rows.range_check(WIDE_BITS, &d)?;
+                rows.check_at_least(WIDE_BITS, &lambda, &d, Fr::one())?;
+                let t = rows.witness(self.value(|witness| witness.t))?;
+                rows.range_check(NARROW_BITS, &t)?;
+                let ed = rows.mul(&e, &d)?;
+                let tl = rows.mul(&t, &lambda)?;
+                rows.assign(
+                    [Input::Copy(&ed), Input::Copy(&tl), zero()],
+                    Coefficients {
+                        a: Fr::one(),
+                        b: -Fr::one(),
+                        constant: -Fr::one(),
+                        ..Default::default()
+                    },
+                )?;
+
+                // Bind `n` and the context (see `RsaChallenge::bind_context`).
+                n.copy_advice(|| "n", &mut region, config.a, 0)?;
+                RsaChallenge::negate_at_selector(&mut region, config.q_a, || "n", 0)?;
+                RsaChallenge::bind_context(&mut region, &config, 2, [(config.a, config.q_a); 6])?;
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Checks specific to the private-key circuit.
+impl Rows<'_, '_> {
+    /// Check that `x >= y + margin`, given that both `x` and `y + margin` are smaller than `2^bits`.
+    fn check_at_least(
+        &mut self,
+        bits: usize,
+        x: &Number,
+        y: &Number,
+        margin: Fr,
+    ) -> Result<(), Error> {
+        let difference = self.linear((x, Fr::one()), (y, -Fr::one()), -margin)?;
+        self.range_check(bits, &difference)?;
+        Ok(())
+    }
+}
+
+/// Convert the public input from human-readable form to a scalar array.
+pub fn prepare_public_input(
+    n: u128,
+    e: u64,
+    account: Account,
+    domain: Domain,
+) -> [Fr; PRIVATE_KEY_INSTANCE_COUNT] {
+    rsa_public_input::encode_private_key(n, e, &account, &domain)
+        .map(|element| Fr::from_repr(element).unwrap())
+}
+
+fn gcd(mut x: u128, mut y: u128) -> u128 {
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    x
+}
+
+/// Compute `x * y mod modulus` for `x, y < modulus` (without overflowing).
+fn mul_mod(mut x: u128, mut y: u128, modulus: u128) -> u128 {
+    let add_mod = |x: u128, y: u128| {
+        if x >= modulus - y {
+            x - (modulus - y)
+        } else {
+            x + y
+        }
+    };
+    let mut result = 0;
+    while y > 0 {
+        if y & 1 == 1 {
+            result = add_mod(result, x);
+        }
+        x = add_mod(x, x);
+        y >>= 1;
+    }
+    result % modulus
+}
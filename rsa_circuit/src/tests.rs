@@ -1,36 +1,100 @@
 use halo2_proofs::{
-    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    arithmetic::Field,
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        ff::PrimeField,
+    },
     plonk::{verify_proof, Error, VerifyingKey},
     poly::kzg::{commitment::ParamsKZG, multiopen::VerifierGWC, strategy::SingleStrategy},
     transcript::{Blake2bRead, TranscriptReadBuffer},
     SerdeFormat,
 };
+use proptest::prelude::*;
+use rsa_public_input::{encode, encode_bytes, FIELD_ELEMENT_SIZE, INSTANCE_COUNT};
 
-use crate::utils::{generate_proof, generate_setup, prepare_public_input, Setup};
+use crate::{
+    utils::{
+        self, generate_proof, generate_setup, prepare_public_input, Account, Domain,
+        MultiOpenScheme, Setup,
+    },
+    RsaChallenge, CIRCUIT_MAX_K,
+};
+
+mod backend;
+mod batch;
+mod challenge;
+mod golden;
+mod hashed;
+mod info;
+mod mock_prover;
+mod multi_factor;
+mod multiopen;
+mod preimage;
+mod private_key;
+mod transcript;
+mod well_formedness;
 
-const CIRCUIT_MAX_K: u32 = 5;
-const ACCOUNT: [u8; 32] = [1u8; 32];
-const FAKE_ACCOUNT: [u8; 32] = [2u8; 32];
+const ACCOUNT: Account = Account::new([1u8; 32]);
+const FAKE_ACCOUNT: Account = Account::new([2u8; 32]);
+const DOMAIN: Domain = Domain {
+    contract: Account::new([3u8; 32]),
+    chain_id: [4u8; 32],
+};
+/// Another deployment of the same challenge on the same chain.
+const OTHER_CONTRACT: Domain = Domain {
+    contract: Account::new([5u8; 32]),
+    ..DOMAIN
+};
+/// Deployment at the same address, but on another chain.
+const OTHER_CHAIN: Domain = Domain {
+    chain_id: [6u8; 32],
+    ..DOMAIN
+};
 
 struct TestSetup {
     proof: Vec<u8>,
-    instances: [Fr; 3],
+    instances: [Fr; INSTANCE_COUNT],
     vk: VerifyingKey<G1Affine>,
     params: ParamsKZG<Bn256>,
 }
 
-fn pipeline(p: u128, q: u128, account: [u8; 32]) -> TestSetup {
-    let setup = generate_setup(CIRCUIT_MAX_K);
-    let proof = generate_proof(&setup, p, q, account);
+fn pipeline(p: u128, q: u128, account: Account) -> TestSetup {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let proof = generate_proof(&setup, p, q, account, DOMAIN);
 
     TestSetup {
         proof,
-        instances: prepare_public_input(p * q, account),
+        instances: prepare_public_input(p * q, account, DOMAIN),
+        vk: setup.vk,
+        params: setup.params,
+    }
+}
+
+/// Like `pipeline`, but accepts trivial factors. Since `(1-1)^-1` doesn't exist, zero is used instead.
+fn trivial_pipeline(p: u128, q: u128, account: Account) -> TestSetup {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let instances = prepare_public_input(p * q, account, DOMAIN);
+    let (p, q) = (Fr::from_u128(p), Fr::from_u128(q));
+    let circuit = RsaChallenge {
+        p: Some(p),
+        p_dec_inv: Some(dec_inv_or_zero(p)),
+        q: Some(q),
+        q_dec_inv: Some(dec_inv_or_zero(q)),
+    };
+
+    TestSetup {
+        proof: utils::prove(&setup, circuit, &instances),
+        instances,
         vk: setup.vk,
         params: setup.params,
     }
 }
 
+/// Inverse of `x - 1` or zero if it doesn't exist.
+fn dec_inv_or_zero(x: Fr) -> Fr {
+    (x - Fr::one()).invert().unwrap_or(Fr::zero())
+}
+
 fn verify(setup: TestSetup) -> Result<(), Error> {
     verify_proof::<_, VerifierGWC<_>, _, _, _>(
         &setup.params,
@@ -55,12 +119,24 @@ fn works_with_big_numbers() {
 fn does_not_accept_fake_account() {
     let true_setup = pipeline(41, 43, ACCOUNT);
     let fake_setup = TestSetup {
-        instances: prepare_public_input(41 * 43, FAKE_ACCOUNT),
+        instances: prepare_public_input(41 * 43, FAKE_ACCOUNT, DOMAIN),
         ..true_setup
     };
     assert!(verify(fake_setup).is_err());
 }
 
+#[test]
+fn does_not_accept_other_domain() {
+    for domain in [OTHER_CONTRACT, OTHER_CHAIN] {
+        let true_setup = pipeline(41, 43, ACCOUNT);
+        let replayed_setup = TestSetup {
+            instances: prepare_public_input(41 * 43, ACCOUNT, domain),
+            ..true_setup
+        };
+        assert!(verify(replayed_setup).is_err(), "{domain:?}");
+    }
+}
+
 #[test]
 fn does_not_accept_incorrect_proof() {
     let true_setup = pipeline(41, 43, ACCOUNT);
@@ -74,16 +150,92 @@ fn does_not_accept_incorrect_proof() {
 
 #[test]
 fn does_not_accept_trivial_factorization() {
-    assert!(verify(pipeline(1, 13, ACCOUNT)).is_err());
-    assert!(verify(pipeline(13, 1, ACCOUNT)).is_err());
+    assert!(verify(trivial_pipeline(1, 13, ACCOUNT)).is_err());
+    assert!(verify(trivial_pipeline(13, 1, ACCOUNT)).is_err());
+}
+
+#[test]
+fn verification_helper_checks_statement() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let proof = generate_proof(&setup, 41, 43, ACCOUNT, DOMAIN);
+
+    assert!(utils::verify_proof(
+        &setup,
+        &proof,
+        41 * 43,
+        ACCOUNT,
+        DOMAIN
+    ));
+    assert!(!utils::verify_proof(
+        &setup,
+        &proof,
+        41 * 43,
+        FAKE_ACCOUNT,
+        DOMAIN
+    ));
+    assert!(!utils::verify_proof(
+        &setup,
+        &proof,
+        11 * 13,
+        ACCOUNT,
+        DOMAIN
+    ));
+    assert!(!utils::verify_proof(
+        &setup,
+        &proof,
+        41 * 43,
+        ACCOUNT,
+        OTHER_CONTRACT
+    ));
+    assert!(!utils::verify_proof(
+        &setup,
+        &proof,
+        41 * 43,
+        ACCOUNT,
+        OTHER_CHAIN
+    ));
+}
+
+#[test]
+fn serialized_verification_checks_statement() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let proof = generate_proof(&setup, 41, 43, ACCOUNT, DOMAIN);
+    let vk = setup.serialize_vk();
+
+    assert!(utils::verify_serialized(
+        &vk,
+        &proof,
+        &encode_bytes(41 * 43, &ACCOUNT, &DOMAIN)
+    ));
+    assert!(!utils::verify_serialized(
+        &vk,
+        &proof,
+        &encode_bytes(41 * 43, &FAKE_ACCOUNT, &DOMAIN)
+    ));
+    assert!(!utils::verify_serialized(
+        &vk,
+        &proof,
+        &encode_bytes(41 * 43, &ACCOUNT, &OTHER_CONTRACT)
+    ));
+    assert!(!utils::verify_serialized(
+        &vk,
+        &proof,
+        &[0xff; INSTANCE_COUNT * FIELD_ELEMENT_SIZE]
+    ));
+    assert!(!utils::verify_serialized(
+        &vk[..4],
+        &proof,
+        &encode_bytes(41 * 43, &ACCOUNT, &DOMAIN)
+    ));
 }
 
 #[test]
 fn setup_serialization_works() {
-    let setup = generate_setup(CIRCUIT_MAX_K);
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
     let serialized = setup.clone().to_bytes();
-    let deserialized = Setup::from_bytes(&mut serialized.as_slice());
+    let deserialized = Setup::from_bytes(&mut serialized.as_slice()).unwrap();
 
+    assert_eq!(setup.scheme, deserialized.scheme);
     assert_eq!(setup.k, deserialized.k);
     assert_eq!(setup.params.s_g2(), deserialized.params.s_g2());
     assert_eq!(
@@ -91,3 +243,20 @@ fn setup_serialization_works() {
         deserialized.pk.to_bytes(SerdeFormat::RawBytesUnchecked)
     );
 }
+
+proptest! {
+    #[test]
+    fn public_input_matches_contract_encoding(n: u128, account: [u8; 32], contract: [u8; 32]) {
+        let account = Account::new(account);
+        let domain = Domain { contract: Account::new(contract), ..DOMAIN };
+        let instances = prepare_public_input(n, account, domain);
+
+        for (instance, element) in instances.iter().zip(encode(n, &account, &domain)) {
+            prop_assert_eq!(instance.to_repr(), element);
+        }
+        prop_assert_eq!(
+            instances.iter().flat_map(|instance| instance.to_repr()).collect::<Vec<_>>(),
+            encode_bytes(n, &account, &domain).to_vec()
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use super::{ACCOUNT, CIRCUIT_MAX_K, DOMAIN, FAKE_ACCOUNT};
+use crate::backend::{hash_to_curve, Backend, BackendSetup};
+
+const BACKENDS: [Backend; 2] = [Backend::Kzg, Backend::Ipa];
+
+#[test]
+fn every_backend_checks_statement() {
+    for backend in BACKENDS {
+        let setup = BackendSetup::generate(CIRCUIT_MAX_K, backend);
+        let proof = setup.generate_proof(41, 43, ACCOUNT, DOMAIN);
+
+        assert!(
+            setup.verify_proof(&proof, 41 * 43, ACCOUNT, DOMAIN),
+            "{backend:?}"
+        );
+        assert!(
+            !setup.verify_proof(&proof, 41 * 43, FAKE_ACCOUNT, DOMAIN),
+            "{backend:?}"
+        );
+        assert!(
+            !setup.verify_proof(&proof, 11 * 13, ACCOUNT, DOMAIN),
+            "{backend:?}"
+        );
+    }
+}
+
+#[test]
+fn proof_is_bound_to_backend() {
+    let kzg = BackendSetup::generate(CIRCUIT_MAX_K, Backend::Kzg);
+    let ipa = BackendSetup::generate(CIRCUIT_MAX_K, Backend::Ipa);
+
+    assert!(!ipa.verify_proof(
+        &kzg.generate_proof(41, 43, ACCOUNT, DOMAIN),
+        41 * 43,
+        ACCOUNT,
+        DOMAIN
+    ));
+    assert!(!kzg.verify_proof(
+        &ipa.generate_proof(41, 43, ACCOUNT, DOMAIN),
+        41 * 43,
+        ACCOUNT,
+        DOMAIN
+    ));
+}
+
+#[test]
+fn serialization_preserves_backend() {
+    for backend in BACKENDS {
+        let serialized = BackendSetup::generate(CIRCUIT_MAX_K, backend).to_bytes();
+        let deserialized = BackendSetup::from_bytes(&mut serialized.as_slice()).unwrap();
+
+        assert_eq!(deserialized.backend(), backend);
+        let proof = deserialized.generate_proof(41, 43, ACCOUNT, DOMAIN);
+        assert!(deserialized.verify_proof(&proof, 41 * 43, ACCOUNT, DOMAIN));
+    }
+}
+
+#[test]
+fn ipa_setup_is_transparent() {
+    // Anybody can rederive the parameters, so two independent setups are identical.
+    let first = BackendSetup::generate(CIRCUIT_MAX_K, Backend::Ipa).to_bytes();
+    let second = BackendSetup::generate(CIRCUIT_MAX_K, Backend::Ipa).to_bytes();
+    assert_eq!(first, second);
+
+    assert_eq!(hash_to_curve(b"generator"), hash_to_curve(b"generator"));
+    assert_ne!(
+        hash_to_curve(b"generator"),
+        hash_to_curve(b"another generator")
+    );
+}
@@ -0,0 +1,52 @@
+use super::{ACCOUNT, CIRCUIT_MAX_K, DOMAIN, FAKE_ACCOUNT, OTHER_CONTRACT};
+use crate::{
+    batch::Submission,
+    utils::{generate_proof, generate_setup, verify_batch, MultiOpenScheme, Setup},
+};
+
+fn submissions(setup: &Setup) -> Vec<Submission> {
+    [
+        (41, 43, ACCOUNT),
+        (11, 13, FAKE_ACCOUNT),
+        (7, 1000003, ACCOUNT),
+    ]
+    .into_iter()
+    .map(|(p, q, account)| (generate_proof(setup, p, q, account, DOMAIN), p * q, account))
+    .collect()
+}
+
+#[test]
+fn batch_verification_rejects_other_domain() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let submissions = submissions(&setup);
+
+    assert_eq!(
+        verify_batch(&setup, OTHER_CONTRACT, &submissions),
+        vec![0, 1, 2]
+    );
+}
+
+#[test]
+fn batch_verification_accepts_valid_proofs() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+
+    assert_eq!(
+        verify_batch(&setup, DOMAIN, &submissions(&setup)),
+        Vec::<usize>::new()
+    );
+    assert_eq!(verify_batch(&setup, DOMAIN, &[]), Vec::<usize>::new());
+}
+
+#[test]
+fn batch_verification_finds_invalid_proofs() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let mut submissions = [submissions(&setup), submissions(&setup)].concat();
+    // Wrong statement.
+    submissions[1].2 = ACCOUNT;
+    // Malformed proof.
+    submissions[3].0.truncate(10);
+    // Proof of another statement.
+    submissions[4].0 = submissions[0].0.clone();
+
+    assert_eq!(verify_batch(&setup, DOMAIN, &submissions), vec![1, 3, 4]);
+}
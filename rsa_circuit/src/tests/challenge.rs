@@ -0,0 +1,212 @@
+//! Tests of the common puzzle interface: every implementation goes through the same pipeline.
+
+use halo2_proofs::halo2curves::{bn256::Fr, ff::PrimeField};
+use rsa_public_input::{hash_preimage, MODULUS};
+
+use super::{ACCOUNT, DOMAIN, FAKE_ACCOUNT, OTHER_CHAIN, OTHER_CONTRACT};
+use crate::{
+    challenge::{
+        ChallengeCircuit, Factoring, HashPreimage, HashedFactoring, MultiFactor, PrivateKey,
+        WellFormedness,
+    },
+    private_key::PublicKey,
+    utils::{self, verify_serialized, MultiOpenScheme, Setup},
+    well_formedness::Semiprime,
+    CIRCUIT_MAX_K,
+};
+
+/// Prove `statement` with `witness` and check the proof against both the right and the `wrong` statement, locally and
+/// with the serialized public input (as the contract does). Returns the setup and the proof for further checks.
+fn check_pipeline<C: ChallengeCircuit>(
+    statement: &C::Statement,
+    witness: &C::Witness,
+    wrong: &C::Statement,
+) -> (Setup, Vec<u8>) {
+    let setup = C::generate_setup(MultiOpenScheme::Gwc);
+    let proof = C::generate_proof(&setup, statement, witness, ACCOUNT, DOMAIN).unwrap();
+
+    assert!(C::verify_proof(&setup, &proof, statement, ACCOUNT, DOMAIN));
+    assert!(!C::verify_proof(&setup, &proof, wrong, ACCOUNT, DOMAIN));
+
+    let vk = setup.serialize_vk();
+    assert!(verify_serialized(
+        &vk,
+        &proof,
+        &C::encode_bytes(statement, &ACCOUNT, &DOMAIN)
+    ));
+    assert!(!verify_serialized(
+        &vk,
+        &proof,
+        &C::encode_bytes(wrong, &ACCOUNT, &DOMAIN)
+    ));
+
+    (setup, proof)
+}
+
+/// Like `check_pipeline`, but also check that the proof is bound to the participant and the deployment.
+fn check_bound_pipeline<C: ChallengeCircuit>(
+    statement: &C::Statement,
+    witness: &C::Witness,
+    wrong: &C::Statement,
+) {
+    let (setup, proof) = check_pipeline::<C>(statement, witness, wrong);
+
+    assert!(!C::verify_proof(
+        &setup,
+        &proof,
+        statement,
+        FAKE_ACCOUNT,
+        DOMAIN
+    ));
+    assert!(!C::verify_proof(
+        &setup,
+        &proof,
+        statement,
+        ACCOUNT,
+        OTHER_CONTRACT
+    ));
+    assert!(!C::verify_proof(
+        &setup,
+        &proof,
+        statement,
+        ACCOUNT,
+        OTHER_CHAIN
+    ));
+    assert!(!verify_serialized(
+        &setup.serialize_vk(),
+        &proof,
+        &C::encode_bytes(statement, &FAKE_ACCOUNT, &DOMAIN)
+    ));
+}
+
+#[test]
+fn factoring_pipeline() {
+    check_bound_pipeline::<Factoring>(&(41 * 43), &(41, 43), &(41 * 43 + 1));
+}
+
+#[test]
+fn hashed_factoring_pipeline() {
+    check_bound_pipeline::<HashedFactoring>(&(41 * 43), &(41, 43), &(11 * 13));
+}
+
+#[test]
+fn multi_factor_pipeline() {
+    check_bound_pipeline::<MultiFactor<3>>(&(3 * 11 * 7), &[3, 11, 7], &(3 * 11));
+}
+
+#[test]
+fn private_key_pipeline() {
+    let key = PublicKey { n: 61 * 53, e: 17 };
+    let wrong = PublicKey { e: 3, ..key };
+    check_bound_pipeline::<PrivateKey>(&key, &(61, 53, 2753), &wrong);
+}
+
+#[test]
+fn hash_preimage_pipeline() {
+    let preimage = Fr::from(0x1234_5678_9abc_def0).to_repr();
+    let digest = hash_preimage(&preimage).unwrap();
+    check_bound_pipeline::<HashPreimage>(&digest, &preimage, &preimage);
+}
+
+#[test]
+fn well_formedness_pipeline() {
+    let semiprime = Semiprime {
+        n: 101 * 103,
+        factor_bits: 7,
+    };
+    let wrong = Semiprime {
+        factor_bits: 8,
+        ..semiprime
+    };
+    let (setup, proof) = check_pipeline::<WellFormedness>(&semiprime, &(101, 103), &wrong);
+
+    // The proof is about `n` only, so it isn't bound to the context.
+    assert!(WellFormedness::verify_proof(
+        &setup,
+        &proof,
+        &semiprime,
+        FAKE_ACCOUNT,
+        OTHER_CHAIN
+    ));
+}
+
+#[test]
+fn setup_selects_multiopen_scheme() {
+    let key = PublicKey { n: 61 * 53, e: 17 };
+    let setup = PrivateKey::generate_setup(MultiOpenScheme::Shplonk);
+    assert_eq!(setup.scheme, MultiOpenScheme::Shplonk);
+
+    let proof = PrivateKey::generate_proof(&setup, &key, &(61, 53, 2753), ACCOUNT, DOMAIN).unwrap();
+    assert!(PrivateKey::verify_proof(
+        &setup, &proof, &key, ACCOUNT, DOMAIN
+    ));
+
+    let setup = Setup {
+        scheme: MultiOpenScheme::Gwc,
+        ..setup
+    };
+    assert!(!PrivateKey::verify_proof(
+        &setup, &proof, &key, ACCOUNT, DOMAIN
+    ));
+}
+
+#[test]
+fn factoring_matches_dedicated_functions() {
+    let setup = Factoring::generate_setup(MultiOpenScheme::Gwc);
+    let proof = Factoring::generate_proof(&setup, &(41 * 43), &(41, 43), ACCOUNT, DOMAIN).unwrap();
+    assert!(utils::verify_proof(
+        &setup,
+        &proof,
+        41 * 43,
+        ACCOUNT,
+        DOMAIN
+    ));
+
+    let setup = utils::generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let proof = utils::generate_proof(&setup, 41, 43, ACCOUNT, DOMAIN);
+    assert!(Factoring::verify_proof(
+        &setup,
+        &proof,
+        &(41 * 43),
+        ACCOUNT,
+        DOMAIN
+    ));
+}
+
+#[test]
+fn witness_must_solve_statement() {
+    assert!(Factoring::circuit(&(41 * 43), &(41, 43), &ACCOUNT, &DOMAIN).is_some());
+    assert!(Factoring::circuit(&(41 * 43), &(41, 41), &ACCOUNT, &DOMAIN).is_none());
+    assert!(Factoring::circuit(&(41 * 43), &(1, 41 * 43), &ACCOUNT, &DOMAIN).is_none());
+    assert!(Factoring::circuit(&0, &(0, 41), &ACCOUNT, &DOMAIN).is_none());
+
+    let key = PublicKey { n: 61 * 53, e: 17 };
+    assert!(PrivateKey::circuit(&key, &(61, 53, 2753), &ACCOUNT, &DOMAIN).is_some());
+    assert!(PrivateKey::circuit(&key, &(61, 59, 2753), &ACCOUNT, &DOMAIN).is_none());
+    assert!(PrivateKey::circuit(&key, &(61, 53, 2754), &ACCOUNT, &DOMAIN).is_none());
+
+    let digest = hash_preimage(&Fr::from(1763).to_repr()).unwrap();
+    assert!(HashPreimage::circuit(&digest, &Fr::from(1763).to_repr(), &ACCOUNT, &DOMAIN).is_some());
+    assert!(HashPreimage::circuit(&digest, &Fr::from(1764).to_repr(), &ACCOUNT, &DOMAIN).is_none());
+    assert!(HashPreimage::circuit(&digest, &MODULUS, &ACCOUNT, &DOMAIN).is_none());
+
+    assert!(HashedFactoring::circuit(&(41 * 43), &(41, 43), &ACCOUNT, &DOMAIN).is_some());
+    assert!(HashedFactoring::circuit(&(41 * 43), &(1, 41 * 43), &ACCOUNT, &DOMAIN).is_none());
+
+    assert!(MultiFactor::<3>::circuit(&(3 * 11 * 7), &[3, 11, 7], &ACCOUNT, &DOMAIN).is_some());
+    assert!(MultiFactor::<3>::circuit(&(3 * 11 * 7), &[3, 11, 5], &ACCOUNT, &DOMAIN).is_none());
+    assert!(MultiFactor::<2>::circuit(&(3 * 11), &[1, 3 * 11], &ACCOUNT, &DOMAIN).is_none());
+
+    let semiprime = Semiprime {
+        n: 101 * 103,
+        factor_bits: 7,
+    };
+    assert!(WellFormedness::circuit(&semiprime, &(101, 103), &ACCOUNT, &DOMAIN).is_some());
+    assert!(WellFormedness::circuit(&semiprime, &(103, 101), &ACCOUNT, &DOMAIN).is_some());
+    assert!(WellFormedness::circuit(&semiprime, &(101, 101), &ACCOUNT, &DOMAIN).is_none());
+    let wrong = Semiprime {
+        factor_bits: 8,
+        ..semiprime
+    };
+    assert!(WellFormedness::circuit(&wrong, &(101, 103), &ACCOUNT, &DOMAIN).is_none());
+}
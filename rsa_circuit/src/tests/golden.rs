@@ -0,0 +1,127 @@
+//! Reproducibility of seeded setups and proofs, checked against golden files in `fixtures/`.
+//!
+//! A missing or different golden file fails the test. To (re)generate all of them after an intended change of the
+//! format, rerun the tests with `UPDATE_GOLDEN=1` and commit the new files.
+
+use std::{
+    env,
+    fs::{create_dir_all, read, write},
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::{ACCOUNT, CIRCUIT_MAX_K, DOMAIN};
+use crate::{
+    transcript::TranscriptHash,
+    utils::{
+        generate_proof_envelope_with_rng, generate_proof_with_rng, generate_setup,
+        generate_setup_with_rng, verify_envelope, verify_proof, MultiOpenScheme,
+    },
+};
+
+const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(name)
+}
+
+fn check_golden(name: &str, actual: &[u8]) {
+    let path = fixture(name);
+    if env::var_os(UPDATE_ENV).is_some() {
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(&path, actual).unwrap();
+        return;
+    }
+    let Ok(golden) = read(&path) else {
+        panic!("Golden file `{name}` is missing, rerun with `{UPDATE_ENV}=1` to create it");
+    };
+    assert!(
+        golden == actual,
+        "`{name}` differs from the golden file, rerun with `{UPDATE_ENV}=1` if the change is intended"
+    );
+}
+
+fn seeded(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+#[test]
+fn seeded_proofs_are_reproducible() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let proof = generate_proof_with_rng(&setup, 41, 43, ACCOUNT, DOMAIN, seeded(0));
+
+    assert_eq!(
+        proof,
+        generate_proof_with_rng(&setup, 41, 43, ACCOUNT, DOMAIN, seeded(0))
+    );
+    assert_ne!(
+        proof,
+        generate_proof_with_rng(&setup, 41, 43, ACCOUNT, DOMAIN, seeded(1))
+    );
+    assert!(verify_proof(&setup, &proof, 41 * 43, ACCOUNT, DOMAIN));
+}
+
+#[test]
+fn seeded_envelopes_are_reproducible() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    for transcript in [
+        TranscriptHash::Blake2b,
+        TranscriptHash::Keccak256,
+        TranscriptHash::Poseidon,
+    ] {
+        let generate = |seed| {
+            generate_proof_envelope_with_rng(
+                &setup,
+                41,
+                43,
+                ACCOUNT,
+                DOMAIN,
+                transcript,
+                seeded(seed),
+            )
+        };
+        let envelope = generate(0);
+
+        assert!(verify_envelope(&setup, &envelope, 41 * 43, ACCOUNT, DOMAIN));
+        assert_eq!(envelope, generate(0), "{transcript:?}");
+        assert_ne!(envelope, generate(1), "{transcript:?}");
+    }
+}
+
+#[test]
+fn seeded_setups_are_reproducible() {
+    let setup = generate_setup_with_rng(CIRCUIT_MAX_K, MultiOpenScheme::Gwc, seeded(0));
+    let proof = generate_proof_with_rng(&setup, 41, 43, ACCOUNT, DOMAIN, seeded(0));
+    assert!(verify_proof(&setup, &proof, 41 * 43, ACCOUNT, DOMAIN));
+
+    let serialized = setup.to_bytes();
+    assert_eq!(
+        serialized,
+        generate_setup_with_rng(CIRCUIT_MAX_K, MultiOpenScheme::Gwc, seeded(0)).to_bytes()
+    );
+    assert_ne!(
+        serialized,
+        generate_setup_with_rng(CIRCUIT_MAX_K, MultiOpenScheme::Gwc, seeded(1)).to_bytes()
+    );
+}
+
+#[test]
+fn vk_matches_golden_file() {
+    check_golden(
+        "vk.bin",
+        &generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc).serialize_vk(),
+    );
+}
+
+#[test]
+fn proof_matches_golden_file() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let proof = generate_proof_with_rng(&setup, 41, 43, ACCOUNT, DOMAIN, seeded(0));
+    check_golden("proof.bin", &proof);
+
+    let golden = read(fixture("proof.bin")).unwrap();
+    assert!(verify_proof(&setup, &golden, 41 * 43, ACCOUNT, DOMAIN));
+}
@@ -0,0 +1,46 @@
+//! Tests of the hashed public input mode: constraint-level checks with `MockProver`.
+
+use halo2_proofs::dev::MockProver;
+
+use super::{ACCOUNT, DOMAIN, FAKE_ACCOUNT, OTHER_CHAIN, OTHER_CONTRACT};
+use crate::{
+    hashed::{prepare_public_input, HashedRsaChallenge, HASHED_CIRCUIT_K},
+    utils::{Account, Domain},
+};
+
+/// Run the mock prover for `circuit` against the challenge `n` bound to `account` and `domain`.
+fn is_satisfied(circuit: &HashedRsaChallenge, n: u128, account: Account, domain: Domain) -> bool {
+    let instances = prepare_public_input(n, account, domain);
+    MockProver::run(HASHED_CIRCUIT_K, circuit, vec![instances.to_vec()])
+        .expect("Synthesis should not fail")
+        .verify()
+        .is_ok()
+}
+
+#[test]
+fn honest_witness_satisfies_circuit() {
+    let circuit = HashedRsaChallenge::new(41, 43, ACCOUNT, DOMAIN);
+    assert!(is_satisfied(&circuit, 41 * 43, ACCOUNT, DOMAIN));
+
+    let circuit = HashedRsaChallenge::new(
+        7413101572609314289,
+        6786072055295288333,
+        FAKE_ACCOUNT,
+        OTHER_CHAIN,
+    );
+    assert!(is_satisfied(
+        &circuit,
+        7413101572609314289 * 6786072055295288333,
+        FAKE_ACCOUNT,
+        OTHER_CHAIN
+    ));
+}
+
+#[test]
+fn wrong_statement_is_rejected() {
+    let circuit = HashedRsaChallenge::new(41, 43, ACCOUNT, DOMAIN);
+    assert!(!is_satisfied(&circuit, 11 * 13, ACCOUNT, DOMAIN));
+    assert!(!is_satisfied(&circuit, 41 * 43, FAKE_ACCOUNT, DOMAIN));
+    assert!(!is_satisfied(&circuit, 41 * 43, ACCOUNT, OTHER_CONTRACT));
+    assert!(!is_satisfied(&circuit, 41 * 43, ACCOUNT, OTHER_CHAIN));
+}
@@ -0,0 +1,64 @@
+//! Tests of the cost report: the measured sizes must match the real table and proofs.
+
+use halo2_proofs::{
+    dev::MockProver,
+    halo2curves::{bn256::Fr, ff::PrimeField},
+};
+use rsa_public_input::{hash_preimage, INSTANCE_COUNT, PREIMAGE_INSTANCE_COUNT};
+
+use super::{ACCOUNT, DOMAIN};
+use crate::{
+    challenge::{ChallengeCircuit, Factoring, HashPreimage, PrivateKey},
+    private_key::PublicKey,
+    utils::MultiOpenScheme,
+    CIRCUIT_MAX_K,
+};
+
+/// Check the report of the puzzle `C` against a real proof of `statement`.
+fn check_report<C: ChallengeCircuit>(statement: C::Statement, witness: C::Witness) {
+    let info = C::info();
+    assert_eq!(info.k, C::K);
+    assert!(info.min_k <= C::K, "{}", C::NAME);
+    assert!(info.rows <= info.usable_rows, "{}", C::NAME);
+    assert_eq!(
+        info.instances,
+        C::encode(&statement, &ACCOUNT, &DOMAIN).len(),
+        "{}",
+        C::NAME
+    );
+
+    let setup = C::generate_setup(MultiOpenScheme::Gwc);
+    let proof = C::generate_proof(&setup, &statement, &witness, ACCOUNT, DOMAIN).unwrap();
+    assert_eq!(info.proof_size, proof.len(), "{}", C::NAME);
+}
+
+#[test]
+fn report_matches_real_proofs() {
+    check_report::<Factoring>(41 * 43, (41, 43));
+    check_report::<PrivateKey>(PublicKey { n: 61 * 53, e: 17 }, (61, 53, 2753));
+    let preimage = Fr::from(1763).to_repr();
+    check_report::<HashPreimage>(hash_preimage(&preimage).unwrap(), preimage);
+}
+
+#[test]
+fn report_describes_standard_plonk() {
+    let info = Factoring::info();
+    assert_eq!(info.advice_columns, 3);
+    assert_eq!(info.instance_columns, 1);
+    assert_eq!(info.max_degree, 3);
+    assert_eq!(info.instances, INSTANCE_COUNT);
+    assert_eq!(HashPreimage::info().instances, PREIMAGE_INSTANCE_COUNT);
+}
+
+#[test]
+fn minimum_k_fits_exactly() {
+    let info = Factoring::info();
+    assert!(info.min_k <= CIRCUIT_MAX_K);
+
+    let circuit = Factoring::circuit(&(41 * 43), &(41, 43), &ACCOUNT, &DOMAIN).unwrap();
+    let instances = Factoring::prepare_public_input(&(41 * 43), ACCOUNT, DOMAIN);
+    let prover = MockProver::run(info.min_k, &circuit, vec![instances.clone()])
+        .expect("Synthesis should not fail");
+    assert!(prover.verify().is_ok());
+    assert!(MockProver::run(info.min_k - 1, &circuit, vec![instances]).is_err());
+}
@@ -0,0 +1,210 @@
+//! Constraint-level tests of `RsaChallenge` run with `MockProver`.
+//!
+//! Unlike the proving pipeline, the mock prover reports exactly which constraints are violated, so here we can check
+//! that every dishonest witness is caught by the right gate in the right row.
+
+use halo2_proofs::{
+    dev::{FailureLocation, MockProver, VerifyFailure},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+};
+
+use super::{dec_inv_or_zero, ACCOUNT, CIRCUIT_MAX_K, DOMAIN, OTHER_CHAIN, OTHER_CONTRACT};
+use crate::{
+    utils::{prepare_public_input, Domain},
+    RsaChallenge,
+};
+
+/// Row of the `p * q = n` check.
+const PRODUCT_ROW: usize = 0;
+/// Row of the `p != 1` check.
+const P_NON_TRIVIALITY_ROW: usize = 3;
+/// Row of the `q != 1` check.
+const Q_NON_TRIVIALITY_ROW: usize = 4;
+
+/// Simplified `VerifyFailure`, pointing to the row in the (only) region of the circuit.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Failure {
+    /// A gate is not satisfied.
+    Gate(usize),
+    /// An equality constraint is not satisfied.
+    Copy(usize),
+}
+
+/// Arbitrary assignment of the private inputs, not necessarily satisfying the circuit.
+struct Witness {
+    p: Fr,
+    p_dec_inv: Fr,
+    q: Fr,
+    q_dec_inv: Fr,
+}
+
+impl Witness {
+    /// Assignment with correctly computed inverses (or zeros, where they don't exist).
+    fn honest(p: Fr, q: Fr) -> Self {
+        Self {
+            p,
+            p_dec_inv: dec_inv_or_zero(p),
+            q,
+            q_dec_inv: dec_inv_or_zero(q),
+        }
+    }
+
+    fn circuit(&self) -> RsaChallenge {
+        RsaChallenge {
+            p: Some(self.p),
+            p_dec_inv: Some(self.p_dec_inv),
+            q: Some(self.q),
+            q_dec_inv: Some(self.q_dec_inv),
+        }
+    }
+}
+
+/// Run the mock prover for `witness` against the challenge `n` and return all the failures (sorted).
+fn failures(witness: Witness, n: Fr) -> Vec<Failure> {
+    let mut instances = prepare_public_input(0, ACCOUNT, DOMAIN);
+    instances[0] = n;
+
+    let prover = MockProver::run(CIRCUIT_MAX_K, &witness.circuit(), vec![instances.to_vec()])
+        .expect("Synthesis should not fail");
+    let mut failures = prover
+        .verify()
+        .err()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|failure| match failure {
+            VerifyFailure::ConstraintNotSatisfied {
+                location: FailureLocation::InRegion { offset, .. },
+                ..
+            } => Failure::Gate(offset),
+            VerifyFailure::Permutation {
+                location: FailureLocation::InRegion { offset, .. },
+                ..
+            } => Failure::Copy(offset),
+            other => panic!("Unexpected failure: {other:?}"),
+        })
+        .collect::<Vec<_>>();
+    failures.sort();
+    failures.dedup();
+    failures
+}
+
+fn fr(x: u128) -> Fr {
+    Fr::from_u128(x)
+}
+
+#[test]
+fn honest_witness_satisfies_circuit() {
+    assert_eq!(failures(Witness::honest(fr(41), fr(43)), fr(1763)), vec![]);
+    assert_eq!(
+        failures(
+            Witness::honest(fr(7413101572609314289), fr(6786072055295288333)),
+            fr(7413101572609314289 * 6786072055295288333)
+        ),
+        vec![]
+    );
+}
+
+#[test]
+fn honest_witness_satisfies_circuit_with_any_domain() {
+    // Every instance is cancelled in the row with its own index, so a non-zero domain must not leak into the rows of
+    // the other checks.
+    let circuit = Witness::honest(fr(41), fr(43)).circuit();
+    for domain in [Domain::default(), DOMAIN, OTHER_CONTRACT, OTHER_CHAIN] {
+        let instances = prepare_public_input(1763, ACCOUNT, domain);
+        let prover = MockProver::run(CIRCUIT_MAX_K, &circuit, vec![instances.to_vec()])
+            .expect("Synthesis should not fail");
+        assert!(prover.verify().is_ok(), "{domain:?}");
+    }
+}
+
+#[test]
+fn wrong_product_is_caught() {
+    assert_eq!(
+        failures(Witness::honest(fr(41), fr(43)), fr(1764)),
+        vec![Failure::Gate(PRODUCT_ROW)]
+    );
+    assert_eq!(
+        failures(Witness::honest(fr(41), fr(41)), fr(1763)),
+        vec![Failure::Gate(PRODUCT_ROW)]
+    );
+}
+
+#[test]
+fn wrong_inverse_is_caught() {
+    let witness = Witness {
+        p_dec_inv: fr(2),
+        ..Witness::honest(fr(41), fr(43))
+    };
+    assert_eq!(
+        failures(witness, fr(1763)),
+        vec![Failure::Gate(P_NON_TRIVIALITY_ROW)]
+    );
+
+    let witness = Witness {
+        q_dec_inv: Fr::zero(),
+        ..Witness::honest(fr(41), fr(43))
+    };
+    assert_eq!(
+        failures(witness, fr(1763)),
+        vec![Failure::Gate(Q_NON_TRIVIALITY_ROW)]
+    );
+
+    let witness = Witness {
+        p_dec_inv: dec_inv_or_zero(fr(43)),
+        q_dec_inv: dec_inv_or_zero(fr(41)),
+        ..Witness::honest(fr(41), fr(43))
+    };
+    assert_eq!(
+        failures(witness, fr(1763)),
+        vec![
+            Failure::Gate(P_NON_TRIVIALITY_ROW),
+            Failure::Gate(Q_NON_TRIVIALITY_ROW)
+        ]
+    );
+}
+
+#[test]
+fn trivial_factors_are_caught() {
+    assert_eq!(
+        failures(Witness::honest(fr(1), fr(1763)), fr(1763)),
+        vec![Failure::Gate(P_NON_TRIVIALITY_ROW)]
+    );
+    assert_eq!(
+        failures(Witness::honest(fr(1763), fr(1)), fr(1763)),
+        vec![Failure::Gate(Q_NON_TRIVIALITY_ROW)]
+    );
+    assert_eq!(
+        failures(Witness::honest(fr(1), fr(1)), fr(1)),
+        vec![
+            Failure::Gate(P_NON_TRIVIALITY_ROW),
+            Failure::Gate(Q_NON_TRIVIALITY_ROW)
+        ]
+    );
+}
+
+#[test]
+fn zero_factor_is_caught() {
+    assert_eq!(
+        failures(Witness::honest(Fr::zero(), fr(43)), fr(1763)),
+        vec![Failure::Gate(PRODUCT_ROW)]
+    );
+    assert_eq!(
+        failures(Witness::honest(fr(41), Fr::zero()), fr(1763)),
+        vec![Failure::Gate(PRODUCT_ROW)]
+    );
+}
+
+#[test]
+fn wraparound_product_is_caught() {
+    // `(r-1) * n = -n (mod r)`, which is not `n`.
+    let minus_one = -Fr::one();
+    assert_eq!(
+        failures(Witness::honest(minus_one, fr(1763)), fr(1763)),
+        vec![Failure::Gate(PRODUCT_ROW)]
+    );
+    // `(r-1) * (r-1) = 1 (mod r)`, which is not `n`.
+    assert_eq!(
+        failures(Witness::honest(minus_one, minus_one), fr(1763)),
+        vec![Failure::Gate(PRODUCT_ROW)]
+    );
+}
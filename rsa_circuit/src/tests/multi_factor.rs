@@ -0,0 +1,82 @@
+//! Tests of the multi-factor circuit: constraint-level checks with `MockProver`.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::Value,
+    dev::MockProver,
+    halo2curves::{bn256::Fr, ff::PrimeField},
+};
+
+use super::{ACCOUNT, DOMAIN};
+use crate::{
+    multi_factor::{MultiFactorChallenge, MAX_FACTOR_COUNT, MULTI_FACTOR_K},
+    utils::prepare_public_input,
+};
+
+/// Run the mock prover for `circuit` against the challenge `n`.
+fn is_satisfied(circuit: &MultiFactorChallenge, n: u128) -> bool {
+    let instances = prepare_public_input(n, ACCOUNT, DOMAIN);
+    MockProver::run(MULTI_FACTOR_K, circuit, vec![instances.to_vec()])
+        .expect("Synthesis should not fail")
+        .verify()
+        .is_ok()
+}
+
+/// Circuit with the given factors, in the given order and without any validation.
+fn forged(factors: &[Fr]) -> MultiFactorChallenge {
+    MultiFactorChallenge {
+        factors: factors.iter().copied().map(Value::known).collect(),
+    }
+}
+
+#[test]
+fn honest_witness_satisfies_circuit() {
+    let circuit = MultiFactorChallenge::new(&[41, 43]).unwrap();
+    assert!(is_satisfied(&circuit, 41 * 43));
+
+    let circuit = MultiFactorChallenge::new(&[3, 11, 7, 3]).unwrap();
+    assert!(is_satisfied(&circuit, 3 * 11 * 7 * 3));
+
+    let circuit = MultiFactorChallenge::new(&[2; MAX_FACTOR_COUNT]).unwrap();
+    assert!(is_satisfied(&circuit, 1 << MAX_FACTOR_COUNT));
+
+    let (big, small) = ((1 << 100) + 277, 65521);
+    let circuit = MultiFactorChallenge::new(&[small, big]).unwrap();
+    assert!(is_satisfied(&circuit, big * small));
+}
+
+#[test]
+fn wrong_statement_is_rejected() {
+    let circuit = MultiFactorChallenge::new(&[3, 11, 7]).unwrap();
+    assert!(!is_satisfied(&circuit, 3 * 11 * 7 + 1));
+    assert!(!is_satisfied(&circuit, 3 * 11));
+}
+
+#[test]
+fn invalid_factors_have_no_witness() {
+    assert!(MultiFactorChallenge::new(&[1763]).is_none());
+    assert!(MultiFactorChallenge::new(&[41, 43, 1]).is_none());
+    assert!(MultiFactorChallenge::new(&[41, 0]).is_none());
+    assert!(MultiFactorChallenge::new(&[2; MAX_FACTOR_COUNT + 1]).is_none());
+    assert!(MultiFactorChallenge::new(&[u128::MAX, 2]).is_none());
+}
+
+#[test]
+fn trivial_factor_is_rejected() {
+    let circuit = forged(&[Fr::from(1763), Fr::one()]);
+    assert!(!is_satisfied(&circuit, 1763));
+}
+
+#[test]
+fn unsorted_factors_are_rejected() {
+    let circuit = forged(&[Fr::from(41), Fr::from(43)]);
+    assert!(!is_satisfied(&circuit, 41 * 43));
+}
+
+#[test]
+fn wraparound_is_rejected() {
+    // `(-1) * (-n) = n` in the field, but not over the integers.
+    let n = 41 * 43;
+    let circuit = forged(&[-Fr::one(), -Fr::from_u128(n)]);
+    assert!(!is_satisfied(&circuit, n));
+}
@@ -0,0 +1,140 @@
+use super::{ACCOUNT, CIRCUIT_MAX_K, DOMAIN, FAKE_ACCOUNT};
+use crate::utils::{
+    generate_proof, generate_setup, verify_batch, verify_proof, MultiOpenScheme, Setup,
+};
+
+const SCHEMES: [MultiOpenScheme; 2] = [MultiOpenScheme::Gwc, MultiOpenScheme::Shplonk];
+
+/// Length of the header of a serialized setup: magic bytes, format version and the scheme.
+const SETUP_HEADER_LEN: usize = 6;
+
+fn setup_with(scheme: MultiOpenScheme) -> Setup {
+    generate_setup(CIRCUIT_MAX_K, scheme)
+}
+
+#[test]
+fn every_scheme_checks_statement() {
+    for scheme in SCHEMES {
+        let setup = setup_with(scheme);
+        let proof = generate_proof(&setup, 41, 43, ACCOUNT, DOMAIN);
+
+        assert!(
+            verify_proof(&setup, &proof, 41 * 43, ACCOUNT, DOMAIN),
+            "{scheme:?}"
+        );
+        assert!(
+            !verify_proof(&setup, &proof, 41 * 43, FAKE_ACCOUNT, DOMAIN),
+            "{scheme:?}"
+        );
+        assert!(
+            !verify_proof(&setup, &proof, 11 * 13, ACCOUNT, DOMAIN),
+            "{scheme:?}"
+        );
+    }
+}
+
+#[test]
+fn proof_is_bound_to_scheme() {
+    let gwc = setup_with(MultiOpenScheme::Gwc);
+    let shplonk = Setup {
+        scheme: MultiOpenScheme::Shplonk,
+        ..gwc.clone()
+    };
+
+    let gwc_proof = generate_proof(&gwc, 41, 43, ACCOUNT, DOMAIN);
+    let shplonk_proof = generate_proof(&shplonk, 41, 43, ACCOUNT, DOMAIN);
+
+    assert!(!verify_proof(
+        &shplonk,
+        &gwc_proof,
+        41 * 43,
+        ACCOUNT,
+        DOMAIN
+    ));
+    assert!(!verify_proof(
+        &gwc,
+        &shplonk_proof,
+        41 * 43,
+        ACCOUNT,
+        DOMAIN
+    ));
+}
+
+#[test]
+fn serialization_preserves_scheme() {
+    for scheme in SCHEMES {
+        let serialized = setup_with(scheme).to_bytes();
+        let deserialized = Setup::from_bytes(&mut serialized.as_slice()).unwrap();
+
+        assert_eq!(deserialized.scheme, scheme);
+        let proof = generate_proof(&deserialized, 41, 43, ACCOUNT, DOMAIN);
+        assert!(verify_proof(
+            &deserialized,
+            &proof,
+            41 * 43,
+            ACCOUNT,
+            DOMAIN
+        ));
+    }
+}
+
+#[test]
+fn every_scheme_can_be_batched() {
+    for scheme in SCHEMES {
+        let setup = setup_with(scheme);
+        let submissions = vec![
+            (
+                generate_proof(&setup, 41, 43, ACCOUNT, DOMAIN),
+                41 * 43,
+                ACCOUNT,
+            ),
+            (
+                generate_proof(&setup, 11, 13, FAKE_ACCOUNT, DOMAIN),
+                11 * 13,
+                FAKE_ACCOUNT,
+            ),
+        ];
+
+        assert!(
+            verify_batch(&setup, DOMAIN, &submissions).is_empty(),
+            "{scheme:?}"
+        );
+    }
+}
+
+#[test]
+fn setup_without_header_is_read_as_gwc() {
+    let serialized = setup_with(MultiOpenScheme::Shplonk).to_bytes();
+    let deserialized = Setup::from_bytes(&mut &serialized[SETUP_HEADER_LEN..]).unwrap();
+
+    assert_eq!(deserialized.scheme, MultiOpenScheme::Gwc);
+    let proof = generate_proof(&deserialized, 41, 43, ACCOUNT, DOMAIN);
+    assert!(verify_proof(
+        &deserialized,
+        &proof,
+        41 * 43,
+        ACCOUNT,
+        DOMAIN
+    ));
+}
+
+#[test]
+fn malformed_setup_is_rejected() {
+    let serialized = setup_with(MultiOpenScheme::Gwc).to_bytes();
+    // Unknown format version, unknown scheme and a truncated setup.
+    for (position, byte) in [(4, 0xff), (5, 0xff)] {
+        let mut malformed = serialized.clone();
+        malformed[position] = byte;
+        assert!(Setup::from_bytes(&mut malformed.as_slice()).is_err());
+    }
+    assert!(Setup::from_bytes(&mut &serialized[..SETUP_HEADER_LEN + 8]).is_err());
+}
+
+#[test]
+fn shplonk_proofs_are_not_bigger() {
+    let [gwc, shplonk] =
+        SCHEMES.map(|scheme| generate_proof(&setup_with(scheme), 41, 43, ACCOUNT, DOMAIN).len());
+
+    // SHPLONK opens all the polynomials with two commitments, GWC needs one per opening point.
+    assert!(shplonk <= gwc);
+}
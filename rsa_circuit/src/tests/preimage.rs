@@ -0,0 +1,62 @@
+//! Tests of the hash-preimage circuit: constraint-level checks with `MockProver`.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    dev::MockProver,
+    halo2curves::{bn256::Fr, ff::PrimeField},
+};
+use rsa_public_input::{hash_preimage, FieldElementBytes, MODULUS};
+
+use super::{ACCOUNT, DOMAIN};
+use crate::{
+    challenge::{ChallengeCircuit, HashPreimage},
+    preimage::{prepare_public_input, PreimageChallenge, PREIMAGE_K},
+    utils::MultiOpenScheme,
+};
+
+/// Run the mock prover for `circuit` against the digest `hash`.
+fn is_satisfied(circuit: &PreimageChallenge, hash: &FieldElementBytes) -> bool {
+    let instances = prepare_public_input(hash, ACCOUNT, DOMAIN).unwrap();
+    MockProver::run(PREIMAGE_K, circuit, vec![instances.to_vec()])
+        .expect("Synthesis should not fail")
+        .verify()
+        .is_ok()
+}
+
+fn element(value: u128) -> FieldElementBytes {
+    Fr::from_u128(value).to_repr()
+}
+
+#[test]
+fn honest_witness_satisfies_circuit() {
+    for preimage in [Fr::zero(), Fr::from(1763), -Fr::one()].map(|x| x.to_repr()) {
+        let circuit = PreimageChallenge::new(&preimage).unwrap();
+        assert!(is_satisfied(&circuit, &hash_preimage(&preimage).unwrap()));
+    }
+}
+
+#[test]
+fn wrong_preimage_is_rejected() {
+    let circuit = PreimageChallenge::new(&element(1764)).unwrap();
+    assert!(!is_satisfied(
+        &circuit,
+        &hash_preimage(&element(1763)).unwrap()
+    ));
+}
+
+#[test]
+fn digest_is_not_accepted_as_preimage() {
+    let digest = hash_preimage(&element(1763)).unwrap();
+    let circuit = PreimageChallenge::new(&digest).unwrap();
+    assert!(!is_satisfied(&circuit, &digest));
+}
+
+#[test]
+fn non_canonical_preimage_has_no_witness() {
+    assert!(PreimageChallenge::new(&MODULUS).is_none());
+    assert!(prepare_public_input(&MODULUS, ACCOUNT, DOMAIN).is_none());
+    let digest = hash_preimage(&element(1763)).unwrap();
+    let setup = HashPreimage::generate_setup(MultiOpenScheme::Gwc);
+    assert!(HashPreimage::generate_proof(&setup, &digest, &MODULUS, ACCOUNT, DOMAIN).is_none());
+    assert!(HashPreimage::encode(&MODULUS, &ACCOUNT, &DOMAIN).is_empty());
+}
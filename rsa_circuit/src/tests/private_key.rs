@@ -0,0 +1,89 @@
+//! Tests of the private-key circuit: constraint-level checks with `MockProver`.
+
+use halo2_proofs::{circuit::Value, dev::MockProver};
+
+use super::{ACCOUNT, DOMAIN};
+use crate::private_key::{
+    prepare_public_input, PrivateKeyChallenge, PrivateKeyWitness, PRIVATE_KEY_K,
+};
+
+/// Textbook RSA key: `p = 61`, `q = 53`, `e = 17` and `d = 2753` (modulo `φ(n)`), i.e. `413` modulo `λ(n) = 780`.
+const SMALL_KEY: (u128, u128, u64, u128) = (61, 53, 17, 2753);
+
+/// Run the mock prover for `circuit` against the public key `(n, e)`.
+fn is_satisfied(circuit: &PrivateKeyChallenge, n: u128, e: u64) -> bool {
+    let instances = prepare_public_input(n, e, ACCOUNT, DOMAIN);
+    MockProver::run(PRIVATE_KEY_K, circuit, vec![instances.to_vec()])
+        .expect("Synthesis should not fail")
+        .verify()
+        .is_ok()
+}
+
+/// Circuit with the given witness, without any validation.
+fn forged(witness: PrivateKeyWitness) -> PrivateKeyChallenge {
+    PrivateKeyChallenge {
+        witness: Value::known(witness),
+    }
+}
+
+#[test]
+fn honest_witness_satisfies_circuit() {
+    let (p, q, e, d) = SMALL_KEY;
+    let circuit = PrivateKeyChallenge::new(p, q, e, d).unwrap();
+    assert!(is_satisfied(&circuit, p * q, e));
+
+    // Already reduced modulo `λ(n)` and with swapped factors.
+    let circuit = PrivateKeyChallenge::new(q, p, e, 413).unwrap();
+    assert!(is_satisfied(&circuit, p * q, e));
+
+    let (p, q, e) = (4294967291, 4294967279, 65537);
+    for d in [108506947230999203, 9331878932546167513] {
+        let circuit = PrivateKeyChallenge::new(p, q, e, d).unwrap();
+        assert!(is_satisfied(&circuit, p * q, e));
+    }
+}
+
+#[test]
+fn invalid_exponent_has_no_witness() {
+    let (p, q, e, d) = SMALL_KEY;
+    assert!(PrivateKeyChallenge::new(p, q, e, d + 1).is_none());
+    assert!(PrivateKeyChallenge::new(p, q, e, 0).is_none());
+    // `e` is not invertible modulo `λ(n)`.
+    assert!(PrivateKeyChallenge::new(p, q, 3, d).is_none());
+    assert!(PrivateKeyChallenge::new(p * q, 1, e, d).is_none());
+    assert!(PrivateKeyChallenge::new(u128::MAX, 3, e, d).is_none());
+}
+
+#[test]
+fn wrong_statement_is_rejected() {
+    let (p, q, e, d) = SMALL_KEY;
+    let circuit = PrivateKeyChallenge::new(p, q, e, d).unwrap();
+    assert!(!is_satisfied(&circuit, p * q + 1, e));
+    assert!(!is_satisfied(&circuit, p * q, e + 2));
+}
+
+#[test]
+fn unreduced_exponent_is_rejected() {
+    // `17 * (413 + 780) = 1 + 26 * 780`, but the exponent is not smaller than `L`.
+    let circuit = forged(PrivateKeyWitness {
+        p: 61,
+        q: 53,
+        g: 4,
+        d: 413 + 780,
+        t: 26,
+    });
+    assert!(!is_satisfied(&circuit, 61 * 53, 17));
+}
+
+#[test]
+fn non_divisor_is_rejected() {
+    // `8` doesn't divide `p - 1 = 60`.
+    let circuit = forged(PrivateKeyWitness {
+        p: 61,
+        q: 53,
+        g: 8,
+        d: 413,
+        t: 9,
+    });
+    assert!(!is_satisfied(&circuit, 61 * 53, 17));
+}
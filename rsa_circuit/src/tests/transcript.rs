@@ -0,0 +1,84 @@
+use super::{ACCOUNT, CIRCUIT_MAX_K, DOMAIN, FAKE_ACCOUNT};
+use crate::{
+    transcript::{ProofEnvelope, TranscriptHash},
+    utils::{
+        generate_proof_envelope, generate_setup, verify_envelope, verify_proof, MultiOpenScheme,
+    },
+};
+
+const TRANSCRIPTS: [TranscriptHash; 3] = [
+    TranscriptHash::Blake2b,
+    TranscriptHash::Keccak256,
+    TranscriptHash::Poseidon,
+];
+
+#[test]
+fn every_transcript_checks_statement() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    for transcript in TRANSCRIPTS {
+        let envelope = generate_proof_envelope(&setup, 41, 43, ACCOUNT, DOMAIN, transcript);
+
+        assert!(
+            verify_envelope(&setup, &envelope, 41 * 43, ACCOUNT, DOMAIN),
+            "{transcript:?}"
+        );
+        assert!(
+            !verify_envelope(&setup, &envelope, 41 * 43, FAKE_ACCOUNT, DOMAIN),
+            "{transcript:?}"
+        );
+        assert!(
+            !verify_envelope(&setup, &envelope, 11 * 13, ACCOUNT, DOMAIN),
+            "{transcript:?}"
+        );
+    }
+}
+
+#[test]
+fn proof_is_bound_to_transcript() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    for transcript in TRANSCRIPTS {
+        let envelope = generate_proof_envelope(&setup, 41, 43, ACCOUNT, DOMAIN, transcript);
+        for other in TRANSCRIPTS.into_iter().filter(|other| *other != transcript) {
+            let relabeled = ProofEnvelope {
+                transcript: other,
+                ..envelope.clone()
+            };
+            assert!(!verify_envelope(
+                &setup,
+                &relabeled,
+                41 * 43,
+                ACCOUNT,
+                DOMAIN
+            ));
+        }
+    }
+}
+
+#[test]
+fn default_transcript_is_compatible_with_raw_proofs() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let envelope =
+        generate_proof_envelope(&setup, 41, 43, ACCOUNT, DOMAIN, TranscriptHash::default());
+
+    assert!(verify_proof(
+        &setup,
+        &envelope.proof,
+        41 * 43,
+        ACCOUNT,
+        DOMAIN
+    ));
+}
+
+#[test]
+fn envelope_serialization_works() {
+    let setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    for transcript in TRANSCRIPTS {
+        let envelope = generate_proof_envelope(&setup, 41, 43, ACCOUNT, DOMAIN, transcript);
+        let deserialized = ProofEnvelope::from_bytes(&envelope.to_bytes());
+
+        assert_eq!(deserialized, Some(envelope));
+    }
+
+    assert_eq!(ProofEnvelope::from_bytes(&[]), None);
+    assert_eq!(ProofEnvelope::from_bytes(&[0xff, 0, 0]), None);
+}
@@ -0,0 +1,80 @@
+//! Tests of the well-formedness circuit: constraint-level checks with `MockProver`.
+
+use halo2_proofs::{circuit::Value, dev::MockProver};
+
+use crate::well_formedness::{
+    prepare_public_input, BaseWitness, FactorWitness, WellFormednessChallenge, BASES,
+    WELL_FORMEDNESS_K,
+};
+
+/// Carmichael number (a Fermat pseudoprime to all the coprime bases) with 10 bits.
+const CARMICHAEL: u64 = 561;
+/// Prime with 10 bits.
+const PRIME: u64 = 523;
+/// Strong pseudoprime to the bases 2, 7 and 61 (`48781 * 97561`), with 33 bits.
+const STRONG_PSEUDOPRIME: u64 = 4759123141;
+
+/// Run the mock prover for `circuit` against the challenge `n` and the stated bit length of the factors.
+fn is_satisfied(circuit: &WellFormednessChallenge, n: u128, factor_bits: u32) -> bool {
+    let instances = prepare_public_input(n, factor_bits).unwrap();
+    MockProver::run(WELL_FORMEDNESS_K, circuit, vec![instances.to_vec()])
+        .expect("Synthesis should not fail")
+        .verify()
+        .is_ok()
+}
+
+#[test]
+fn honest_witness_satisfies_circuit() {
+    let circuit = WellFormednessChallenge::new(101, 103).unwrap();
+    assert!(is_satisfied(&circuit, 101 * 103, 7));
+
+    let circuit = WellFormednessChallenge::new(4294967291, 4294967279).unwrap();
+    assert!(is_satisfied(&circuit, 4294967291 * 4294967279, 32));
+
+    // The two biggest primes below `2^64`.
+    let (p, q) = (18446744073709551557, 18446744073709551533);
+    let circuit = WellFormednessChallenge::new(p, q).unwrap();
+    assert!(is_satisfied(&circuit, p * q, 64));
+}
+
+#[test]
+fn wrong_statement_is_rejected() {
+    let circuit = WellFormednessChallenge::new(101, 103).unwrap();
+    assert!(!is_satisfied(&circuit, 101 * 103 + 2, 7));
+    assert!(!is_satisfied(&circuit, 101 * 103, 8));
+    assert!(!is_satisfied(&circuit, 101 * 103, 32));
+}
+
+#[test]
+fn malformed_factors_have_no_witness() {
+    // Composite factors.
+    assert!(WellFormednessChallenge::new(CARMICHAEL as u128, PRIME as u128).is_none());
+    assert!(FactorWitness::new(STRONG_PSEUDOPRIME).is_none());
+    // Different bit lengths.
+    assert!(WellFormednessChallenge::new(101, PRIME as u128).is_none());
+    // Too small factors.
+    assert!(WellFormednessChallenge::new(41, 43).is_none());
+    // Too big factors.
+    assert!(WellFormednessChallenge::new((1 << 64) + 13, (1 << 64) + 13).is_none());
+}
+
+#[test]
+fn composite_factor_is_rejected() {
+    // `561 - 1 = 35 * 2^4` and `2^35 != 1 (mod 561)`, but a forged witness claims otherwise.
+    let forged = BaseWitness {
+        exponent: 35,
+        two_adicity: 4,
+        flag: false,
+    };
+    let carmichael = FactorWitness {
+        factor: CARMICHAEL,
+        bases: [forged; BASES.len()],
+    };
+    let circuit = WellFormednessChallenge {
+        factors: [
+            Value::known(carmichael),
+            Value::known(FactorWitness::new(PRIME).unwrap()),
+        ],
+    };
+    assert!(!is_satisfied(&circuit, (CARMICHAEL * PRIME) as u128, 10));
+}
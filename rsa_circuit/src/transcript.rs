@@ -0,0 +1,218 @@
+//! Transcripts (Fiat-Shamir hashes) supported for RSA challenge proofs.
+//!
+//! Blake2b is the default and the only one accepted by the on-chain verifier. Keccak256 is the natural choice for EVM
+//! verifiers and Poseidon for verifying proofs inside another circuit. Since a proof is only valid with the transcript
+//! it was generated with, proofs produced with a non-default transcript are wrapped in a [`ProofEnvelope`].
+
+use std::io::{self, Read, Write};
+
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Fr, G1Affine},
+        ff::PrimeField,
+        group::GroupEncoding,
+    },
+    transcript::{
+        Challenge255, EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer,
+        TranscriptWrite, TranscriptWriterBuffer,
+    },
+};
+use rsa_public_input::{field::Scalar, poseidon::Sponge};
+
+/// Hash function used to derive the challenges of a proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TranscriptHash {
+    /// Blake2b (halo2 default). This is the one expected by the on-chain verifier.
+    #[default]
+    Blake2b,
+    /// Keccak256, cheap to recompute in EVM.
+    Keccak256,
+    /// Poseidon over the BN254 scalar field, cheap to recompute in a circuit.
+    Poseidon,
+}
+
+impl TranscriptHash {
+    fn to_byte(self) -> u8 {
+        match self {
+            TranscriptHash::Blake2b => 0,
+            TranscriptHash::Keccak256 => 1,
+            TranscriptHash::Poseidon => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(TranscriptHash::Blake2b),
+            1 => Some(TranscriptHash::Keccak256),
+            2 => Some(TranscriptHash::Poseidon),
+            _ => None,
+        }
+    }
+}
+
+/// Proof together with the transcript hash it was generated with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofEnvelope {
+    /// Transcript hash needed to verify `proof`.
+    pub transcript: TranscriptHash,
+    /// Raw proof.
+    pub proof: Vec<u8>,
+}
+
+impl ProofEnvelope {
+    /// Serialize the envelope to raw bytes: a single byte identifying the transcript followed by the raw proof.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![self.transcript.to_byte()];
+        buffer.extend(&self.proof);
+        buffer
+    }
+
+    /// Deserialize the envelope from raw bytes. Returns `None` if the transcript is unknown.
+    pub fn from_bytes(buffer: &[u8]) -> Option<Self> {
+        let (transcript, proof) = buffer.split_first()?;
+        Some(Self {
+            transcript: TranscriptHash::from_byte(*transcript)?,
+            proof: proof.to_vec(),
+        })
+    }
+}
+
+/// Domain separator of the Poseidon transcript (put in the capacity element of the sponge).
+const POSEIDON_DOMAIN: u128 = u128::from_be_bytes(*b"halo2-transcript");
+
+/// Poseidon sponge absorbing curve points and scalars.
+#[derive(Clone, Debug)]
+struct PoseidonState(Sponge);
+
+impl PoseidonState {
+    fn new() -> Self {
+        Self(Sponge::new(Scalar::from_u128(POSEIDON_DOMAIN)))
+    }
+
+    /// Absorb the compressed encoding of `point`, split into two 128-bit chunks (so that both are valid scalars).
+    fn absorb_point(&mut self, point: G1Affine) {
+        let compressed = point.to_bytes();
+        for chunk in compressed.as_ref().chunks_exact(16) {
+            self.0.absorb(Scalar::from_u128(u128::from_le_bytes(
+                chunk.try_into().unwrap(),
+            )));
+        }
+    }
+
+    fn absorb_scalar(&mut self, scalar: Fr) {
+        let scalar = Scalar::from_bytes(&scalar.to_repr()).expect("`Fr` encoding is canonical");
+        self.0.absorb(scalar);
+    }
+
+    /// Squeeze a challenge. The sponge output is already a uniform field element, so it is passed as is.
+    fn squeeze(&mut self) -> Challenge255<G1Affine> {
+        let mut input = [0u8; 64];
+        input[..32].copy_from_slice(&self.0.squeeze().to_bytes());
+        Challenge255::new(&input)
+    }
+}
+
+/// Poseidon transcript reader.
+#[derive(Debug)]
+pub struct PoseidonRead<R: Read> {
+    state: PoseidonState,
+    reader: R,
+}
+
+/// Poseidon transcript writer.
+#[derive(Debug)]
+pub struct PoseidonWrite<W: Write> {
+    state: PoseidonState,
+    writer: W,
+}
+
+impl<R: Read> Transcript<G1Affine, Challenge255<G1Affine>> for PoseidonRead<R> {
+    fn squeeze_challenge(&mut self) -> Challenge255<G1Affine> {
+        self.state.squeeze()
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.state.absorb_point(point);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.state.absorb_scalar(scalar);
+        Ok(())
+    }
+}
+
+impl<R: Read> TranscriptRead<G1Affine, Challenge255<G1Affine>> for PoseidonRead<R> {
+    fn read_point(&mut self) -> io::Result<G1Affine> {
+        let mut compressed = <G1Affine as GroupEncoding>::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point = Option::from(G1Affine::from_bytes(&compressed)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<Fr> {
+        let mut data = <Fr as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar = Option::from(Fr::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+        Ok(scalar)
+    }
+}
+
+impl<R: Read> TranscriptReadBuffer<R, G1Affine, Challenge255<G1Affine>> for PoseidonRead<R> {
+    fn init(reader: R) -> Self {
+        Self {
+            state: PoseidonState::new(),
+            reader,
+        }
+    }
+}
+
+impl<W: Write> Transcript<G1Affine, Challenge255<G1Affine>> for PoseidonWrite<W> {
+    fn squeeze_challenge(&mut self) -> Challenge255<G1Affine> {
+        self.state.squeeze()
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.state.absorb_point(point);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.state.absorb_scalar(scalar);
+        Ok(())
+    }
+}
+
+impl<W: Write> TranscriptWrite<G1Affine, Challenge255<G1Affine>> for PoseidonWrite<W> {
+    fn write_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.common_point(point)?;
+        self.writer.write_all(point.to_bytes().as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        self.writer.write_all(scalar.to_repr().as_ref())
+    }
+}
+
+impl<W: Write> TranscriptWriterBuffer<W, G1Affine, Challenge255<G1Affine>> for PoseidonWrite<W> {
+    fn init(writer: W) -> Self {
+        Self {
+            state: PoseidonState::new(),
+            writer,
+        }
+    }
+
+    fn finalize(self) -> W {
+        self.writer
+    }
+}
@@ -1,32 +1,78 @@
 //! Helpers for working with the RSA circuit.
 
+use std::io;
+
 use halo2_proofs::{
-    arithmetic::Field,
     halo2curves::{
         bn256::{Bn256, Fr, G1Affine},
         ff::PrimeField,
     },
-    plonk::{create_proof, keygen_pk, keygen_vk, ProvingKey, VerifyingKey},
+    plonk::{self, create_proof, keygen_pk, keygen_vk, Circuit, ProvingKey, VerifyingKey},
     poly::{
         commitment::Params,
-        kzg::{commitment::ParamsKZG, multiopen::ProverGWC},
+        kzg::{
+            commitment::ParamsKZG,
+            multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
     },
     standard_plonk::StandardPlonk,
-    transcript::{Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, Keccak256Read, Keccak256Write, TranscriptRead,
+        TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
     SerdeFormat,
 };
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rsa_public_input::{FIELD_ELEMENT_SIZE, INSTANCE_COUNT};
+
+use crate::{
+    batch::{Accumulator, Submission},
+    transcript::{PoseidonRead, PoseidonWrite, ProofEnvelope, TranscriptHash},
+    RsaChallenge,
+};
+
+pub(crate) const SERDE_FORMAT: SerdeFormat = SerdeFormat::RawBytesUnchecked;
 
-use crate::RsaChallenge;
+/// Magic bytes opening a serialized [`Setup`], followed by the format version.
+const SETUP_MAGIC: &[u8; 4] = b"RSAS";
+/// Version of the [`Setup`] serialization format.
+const SETUP_VERSION: u8 = 1;
 
-const SERDE_FORMAT: SerdeFormat = SerdeFormat::RawBytesUnchecked;
+pub use rsa_public_input::{Account, Domain};
+
+/// Multi-open scheme of the KZG polynomial commitment, i.e. the way all the polynomial openings are batched in a proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultiOpenScheme {
+    /// Gabizon-Williamson-Ciobotaru scheme. This is the one expected by the on-chain verifier.
+    #[default]
+    Gwc,
+    /// Boneh-Drake-Fisch-Gabizon scheme (SHPLONK). Produces proofs with a constant number of opening commitments.
+    Shplonk,
+}
+
+impl MultiOpenScheme {
+    fn to_byte(self) -> u8 {
+        match self {
+            MultiOpenScheme::Gwc => 0,
+            MultiOpenScheme::Shplonk => 1,
+        }
+    }
 
-/// Type representing an identifier of the participant.
-pub type Account = [u8; 32];
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(MultiOpenScheme::Gwc),
+            1 => Some(MultiOpenScheme::Shplonk),
+            _ => None,
+        }
+    }
+}
 
 /// Initial setup artifacts including trusted setup result, proving key and verifying key.
 #[derive(Clone, Debug)]
 pub struct Setup {
+    /// Multi-open scheme used for generating and verifying proofs.
+    pub scheme: MultiOpenScheme,
     /// Logarithm of the maximum number of rows in the PLONK table (polynomial degree).
     pub k: u32,
     /// Proving key.
@@ -38,9 +84,11 @@ pub struct Setup {
 }
 
 impl Setup {
-    /// Serialize setup to raw bytes.
+    /// Serialize setup to raw bytes: a header (magic bytes, format version and the multi-open scheme) followed by the
+    /// SRS and the proving key.
     pub fn to_bytes(self) -> Vec<u8> {
-        let mut buffer = vec![];
+        let mut buffer = SETUP_MAGIC.to_vec();
+        buffer.extend([SETUP_VERSION, self.scheme.to_byte()]);
         self.params
             .write_custom(&mut buffer, SERDE_FORMAT)
             .expect("Failed to save SRS");
@@ -49,20 +97,42 @@ impl Setup {
     }
 
     /// Deserialize setup from raw bytes.
-    pub fn from_bytes(buffer: &mut &[u8]) -> Self {
-        let params =
-            ParamsKZG::<Bn256>::read_custom(buffer, SERDE_FORMAT).expect("Failed to read SRS");
-        let pk = ProvingKey::<G1Affine>::from_bytes::<StandardPlonk>(buffer, SERDE_FORMAT)
-            .expect("Failed to read proving key");
-        Self {
+    ///
+    /// Setups serialized before the multi-open scheme became selectable have no header. They are still accepted, as
+    /// [`MultiOpenScheme::Gwc`] setups.
+    pub fn from_bytes(buffer: &mut &[u8]) -> io::Result<Self> {
+        let bytes: &[u8] = *buffer;
+        let scheme = match bytes.strip_prefix(SETUP_MAGIC.as_slice()) {
+            Some([SETUP_VERSION, scheme, rest @ ..]) => {
+                *buffer = rest;
+                MultiOpenScheme::from_byte(*scheme).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Unknown multi-open scheme")
+                })?
+            }
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Unsupported setup format version",
+                ))
+            }
+            None => MultiOpenScheme::Gwc,
+        };
+
+        let params = ParamsKZG::<Bn256>::read_custom(buffer, SERDE_FORMAT)?;
+        let pk = ProvingKey::<G1Affine>::from_bytes::<StandardPlonk>(buffer, SERDE_FORMAT)?;
+        Ok(Self {
+            scheme,
             k: params.k(),
             vk: pk.get_vk().clone(),
             pk,
             params,
-        }
+        })
     }
 
     /// Serialize verifying key to raw bytes as it is expected by the on-chain verifier.
+    ///
+    /// The verifying key itself doesn't depend on the multi-open scheme, but the on-chain verifier accepts only proofs
+    /// generated with [`MultiOpenScheme::Gwc`].
     pub fn serialize_vk(&self) -> Vec<u8> {
         let mut buffer = vec![];
         buffer.extend(self.k.to_le_bytes());
@@ -71,50 +141,309 @@ impl Setup {
     }
 }
 
-/// Run the initial setup phase (for SRS) and circuit processing (for keys).
-pub fn generate_setup(k: u32) -> Setup {
-    let circuit = RsaChallenge::default();
-    let params = ParamsKZG::<Bn256>::setup(k, ParamsKZG::<Bn256>::mock_rng());
-    let vk = keygen_vk(&params, &circuit).expect("vk generation should not fail");
-    let pk = keygen_pk(&params, vk.clone(), &circuit).expect("pk generation should not fail");
-    Setup { k, pk, vk, params }
+/// Run the initial setup phase (for SRS) and circuit processing (for keys), for proofs with the multi-open `scheme`.
+/// Since the keys don't depend on it, the scheme can still be changed later just by setting [`Setup::scheme`].
+///
+/// The SRS is generated from the mock randomness, which is what the on-chain verifier expects (together with
+/// [`MultiOpenScheme::Gwc`]).
+pub fn generate_setup(k: u32, scheme: MultiOpenScheme) -> Setup {
+    Setup {
+        scheme,
+        ..setup_from_params(
+            ParamsKZG::<Bn256>::setup(k, ParamsKZG::<Bn256>::mock_rng()),
+            &RsaChallenge::default(),
+        )
+    }
+}
+
+/// Like [`generate_setup`], but the SRS is generated from `rng`.
+///
+/// Note that the on-chain verifier (as well as [`verify_serialized`]) always uses the mock SRS, so it won't accept
+/// proofs generated with such setup.
+pub fn generate_setup_with_rng(
+    k: u32,
+    scheme: MultiOpenScheme,
+    rng: impl RngCore + CryptoRng,
+) -> Setup {
+    Setup {
+        scheme,
+        ..setup_from_params(ParamsKZG::<Bn256>::setup(k, rng), &RsaChallenge::default())
+    }
+}
+
+/// Generate keys for `circuit` (without witnesses). The circuit must use the standard PLONK configuration, as this is
+/// what [`Setup::from_bytes`] and the on-chain verifier assume.
+pub(crate) fn setup_from_params(params: ParamsKZG<Bn256>, circuit: &impl Circuit<Fr>) -> Setup {
+    let k = params.k();
+    let vk = keygen_vk(&params, circuit).expect("vk generation should not fail");
+    let pk = keygen_pk(&params, vk.clone(), circuit).expect("pk generation should not fail");
+    Setup {
+        scheme: MultiOpenScheme::default(),
+        k,
+        pk,
+        vk,
+        params,
+    }
 }
 
 /// Convert the public input from human-readable form to a scalar array.
-pub fn prepare_public_input(n: u128, account: Account) -> [Fr; 3] {
-    [
-        Fr::from_u128(n),
-        Fr::from_u128(u128::from_le_bytes(account[..16].try_into().unwrap())),
-        Fr::from_u128(u128::from_le_bytes(account[16..].try_into().unwrap())),
-    ]
-}
-
-/// Generate proof given `setup`, `p`, `q` and `account`.
-pub fn generate_proof(setup: &Setup, p: u128, q: u128, account: Account) -> Vec<u8> {
-    let n = p * q;
-    let circuit = RsaChallenge {
-        p: Some(Fr::from_u128(p)),
-        #[cfg(not(test))]
-        p_dec_inv: Some(Fr::from_u128(p - 1).invert().unwrap()),
-        #[cfg(test)]
-        p_dec_inv: Some(Fr::from_u128(p - 1).invert().unwrap_or(Fr::zero())),
-        q: Some(Fr::from_u128(q)),
-        #[cfg(not(test))]
-        q_dec_inv: Some(Fr::from_u128(q - 1).invert().unwrap()),
-        #[cfg(test)]
-        q_dec_inv: Some(Fr::from_u128(q - 1).invert().unwrap_or(Fr::zero())),
-    };
-    let instances = prepare_public_input(n, account);
+pub fn prepare_public_input(n: u128, account: Account, domain: Domain) -> [Fr; INSTANCE_COUNT] {
+    rsa_public_input::encode(n, &account, &domain).map(|element| Fr::from_repr(element).unwrap())
+}
 
-    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-    create_proof::<_, ProverGWC<'_, Bn256>, _, _, _, _>(
-        &setup.params,
-        &setup.pk,
-        &[circuit],
-        &[&[&instances]],
-        OsRng,
-        &mut transcript,
+/// Generate proof given `setup`, `p`, `q`, `account` and the `domain` (deployment) it is bound to. The proof uses the
+/// default (Blake2b) transcript.
+pub fn generate_proof(
+    setup: &Setup,
+    p: u128,
+    q: u128,
+    account: Account,
+    domain: Domain,
+) -> Vec<u8> {
+    generate_proof_with_rng(setup, p, q, account, domain, OsRng)
+}
+
+/// Like [`generate_proof`], but the blinding factors are drawn from `rng`. With a seeded `rng`, the proof is
+/// reproducible byte by byte.
+pub fn generate_proof_with_rng(
+    setup: &Setup,
+    p: u128,
+    q: u128,
+    account: Account,
+    domain: Domain,
+    rng: impl RngCore + CryptoRng,
+) -> Vec<u8> {
+    let instances = prepare_public_input(p * q, account, domain);
+    prove_with::<Blake2bWrite<_, G1Affine, Challenge255<_>>>(
+        setup,
+        RsaChallenge::new(p, q),
+        &instances,
+        rng,
     )
+}
+
+/// Generate proof given `setup`, `p`, `q`, `account` and `domain` with the chosen `transcript`.
+pub fn generate_proof_envelope(
+    setup: &Setup,
+    p: u128,
+    q: u128,
+    account: Account,
+    domain: Domain,
+    transcript: TranscriptHash,
+) -> ProofEnvelope {
+    generate_proof_envelope_with_rng(setup, p, q, account, domain, transcript, OsRng)
+}
+
+/// Like [`generate_proof_envelope`], but the blinding factors are drawn from `rng`. With a seeded `rng`, the proof is
+/// reproducible byte by byte.
+pub fn generate_proof_envelope_with_rng(
+    setup: &Setup,
+    p: u128,
+    q: u128,
+    account: Account,
+    domain: Domain,
+    transcript: TranscriptHash,
+    rng: impl RngCore + CryptoRng,
+) -> ProofEnvelope {
+    let instances = prepare_public_input(p * q, account, domain);
+    let circuit = RsaChallenge::new(p, q);
+    let proof = match transcript {
+        TranscriptHash::Blake2b => prove_with::<Blake2bWrite<_, G1Affine, Challenge255<_>>>(
+            setup, circuit, &instances, rng,
+        ),
+        TranscriptHash::Keccak256 => prove_with::<Keccak256Write<_, G1Affine, Challenge255<_>>>(
+            setup, circuit, &instances, rng,
+        ),
+        TranscriptHash::Poseidon => {
+            prove_with::<PoseidonWrite<Vec<u8>>>(setup, circuit, &instances, rng)
+        }
+    };
+    ProofEnvelope { transcript, proof }
+}
+
+/// Generate proof for an arbitrary witness assignment and public input.
+pub(crate) fn prove(setup: &Setup, circuit: impl Circuit<Fr>, instances: &[Fr]) -> Vec<u8> {
+    prove_with::<Blake2bWrite<_, G1Affine, Challenge255<_>>>(setup, circuit, instances, OsRng)
+}
+
+fn prove_with<T: TranscriptWriterBuffer<Vec<u8>, G1Affine, Challenge255<G1Affine>>>(
+    setup: &Setup,
+    circuit: impl Circuit<Fr>,
+    instances: &[Fr],
+    rng: impl RngCore + CryptoRng,
+) -> Vec<u8> {
+    let mut transcript = T::init(vec![]);
+    match setup.scheme {
+        MultiOpenScheme::Gwc => create_proof::<_, ProverGWC<'_, Bn256>, _, _, _, _>(
+            &setup.params,
+            &setup.pk,
+            &[circuit],
+            &[&[instances]],
+            rng,
+            &mut transcript,
+        ),
+        MultiOpenScheme::Shplonk => create_proof::<_, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+            &setup.params,
+            &setup.pk,
+            &[circuit],
+            &[&[instances]],
+            rng,
+            &mut transcript,
+        ),
+    }
     .expect("Failed to generate proof");
     transcript.finalize()
 }
+
+/// Verify `proof` (with the default transcript) that `account` knows the factorization of `n` challenged by the
+/// deployment `domain`.
+pub fn verify_proof(
+    setup: &Setup,
+    proof: &[u8],
+    n: u128,
+    account: Account,
+    domain: Domain,
+) -> bool {
+    let instances = prepare_public_input(n, account, domain);
+    verify_with(
+        setup,
+        &mut Blake2bRead::<_, _, Challenge255<_>>::init(proof),
+        &instances,
+    )
+}
+
+/// Verify proof wrapped in `envelope` that `account` knows the factorization of `n` challenged by the deployment
+/// `domain`, using the transcript recorded in the envelope.
+pub fn verify_envelope(
+    setup: &Setup,
+    envelope: &ProofEnvelope,
+    n: u128,
+    account: Account,
+    domain: Domain,
+) -> bool {
+    let instances = prepare_public_input(n, account, domain);
+    let proof = envelope.proof.as_slice();
+    match envelope.transcript {
+        TranscriptHash::Blake2b => verify_with(
+            setup,
+            &mut Blake2bRead::<_, _, Challenge255<_>>::init(proof),
+            &instances,
+        ),
+        TranscriptHash::Keccak256 => verify_with(
+            setup,
+            &mut Keccak256Read::<_, _, Challenge255<_>>::init(proof),
+            &instances,
+        ),
+        TranscriptHash::Poseidon => verify_with(setup, &mut PoseidonRead::init(proof), &instances),
+    }
+}
+
+pub(crate) fn verify_with<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>>(
+    setup: &Setup,
+    transcript: &mut T,
+    instances: &[Fr],
+) -> bool {
+    let strategy = SingleStrategy::new(&setup.params);
+    match setup.scheme {
+        MultiOpenScheme::Gwc => plonk::verify_proof::<_, VerifierGWC<_>, _, _, _>(
+            &setup.params,
+            &setup.vk,
+            strategy,
+            &[&[instances]],
+            transcript,
+        ),
+        MultiOpenScheme::Shplonk => plonk::verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            &setup.params,
+            &setup.vk,
+            strategy,
+            &[&[instances]],
+            transcript,
+        ),
+    }
+    .is_ok()
+}
+
+/// Verify `proof` given the verifying key serialized with [`Setup::serialize_vk`] and the public input encoded with
+/// [`rsa_public_input::encode_bytes`], i.e. in the same way as the on-chain verifier does (in particular, only
+/// [`MultiOpenScheme::Gwc`] proofs are accepted).
+pub fn verify_serialized(vk: &[u8], proof: &[u8], public_input: &[u8]) -> bool {
+    if vk.len() < 4 || public_input.len() % FIELD_ELEMENT_SIZE != 0 {
+        return false;
+    }
+    let (k, mut vk) = vk.split_at(4);
+    let k = u32::from_le_bytes(k.try_into().unwrap());
+    let Ok(vk) = VerifyingKey::<G1Affine>::read::<_, StandardPlonk>(&mut vk, SERDE_FORMAT) else {
+        return false;
+    };
+    let Some(instances) = public_input
+        .chunks_exact(FIELD_ELEMENT_SIZE)
+        .map(|element| Option::from(Fr::from_repr(element.try_into().unwrap())))
+        .collect::<Option<Vec<Fr>>>()
+    else {
+        return false;
+    };
+
+    // The verifier uses the same mock SRS as `generate_setup`.
+    let params = ParamsKZG::<Bn256>::setup(k, ParamsKZG::<Bn256>::mock_rng());
+    plonk::verify_proof::<_, VerifierGWC<_>, _, _, _>(
+        &params,
+        &vk,
+        SingleStrategy::new(&params),
+        &[&[&instances]],
+        &mut Blake2bRead::<_, _, Challenge255<_>>::init(proof),
+    )
+    .is_ok()
+}
+
+/// Verify multiple `submissions` (generated with `setup` for the deployment `domain`) at once. Returns the indices of
+/// the invalid ones.
+///
+/// All the proofs are checked with a single accumulated pairing check (see [`crate::batch`]), which is much
+/// cheaper than verifying them one by one. If the batch turns out to be invalid, it is bisected to find the culprits,
+/// so every invalid proof costs only a logarithmic number of additional batch checks.
+pub fn verify_batch(setup: &Setup, domain: Domain, submissions: &[Submission]) -> Vec<usize> {
+    let mut accumulator = Accumulator::new(setup, domain);
+    let mut well_formed = vec![];
+    let mut invalid = vec![];
+    for (i, (proof, n, account)) in submissions.iter().enumerate() {
+        match accumulator.fold(proof, *n, *account) {
+            Ok(()) => well_formed.push(i),
+            Err(_) => invalid.push(i),
+        }
+    }
+
+    if !accumulator.check() {
+        find_invalid(setup, domain, submissions, &well_formed, &mut invalid);
+    }
+    invalid.sort_unstable();
+    invalid
+}
+
+/// Bisect well-formed submissions at `indices`, which are known to contain an invalid one, and append all the invalid
+/// ones to `invalid`.
+fn find_invalid(
+    setup: &Setup,
+    domain: Domain,
+    submissions: &[Submission],
+    indices: &[usize],
+    invalid: &mut Vec<usize>,
+) {
+    if let [i] = indices {
+        invalid.push(*i);
+        return;
+    }
+
+    let (left, right) = indices.split_at(indices.len() / 2);
+    for half in [left, right] {
+        let mut accumulator = Accumulator::new(setup, domain);
+        for &i in half {
+            let (proof, n, account) = &submissions[i];
+            accumulator
+                .fold(proof, *n, *account)
+                .expect("Proof has already been checked to be well-formed");
+        }
+        if !accumulator.check() {
+            find_invalid(setup, domain, submissions, half, invalid);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//! Companion circuit, in which the deployer proves that the challenge is a well-formed semiprime.
+//!
+//! Nothing stops the deployer from picking an `n` that is much easier to factorize than it looks (e.g. even, with
+//! more than two factors, or with factors of very different sizes). With this circuit they can prove in zero
+//! knowledge that `n = p * q`, where both `p` and `q` are primes with exactly the stated number of bits, without
+//! revealing the factors.
+//!
+//! Primality is checked with the deterministic Miller-Rabin test for the twelve smallest primes (2 to 37) as bases,
+//! which is correct for all numbers below 3.3 * 10^24 (so for all factors of at most [`MAX_FACTOR_BITS`] bits). Since
+//! the test requires the factors to be greater than the bases, they must have at least [`MIN_FACTOR_BITS`] bits.
+//!
+//! Like [`RsaChallenge`](crate::RsaChallenge), the circuit uses only the standard PLONK gate, so its proofs can be
+//! checked by the on-chain verifier (under a separate verifying key).
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, Value},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::{Circuit, ConstraintSystem, Error},
+    standard_plonk::StandardPlonk,
+};
+use rsa_public_input::WELL_FORMEDNESS_INSTANCE_COUNT;
+pub use rsa_public_input::{MAX_FACTOR_BITS, MIN_FACTOR_BITS};
+
+use crate::gadgets::{fr, to_u128, zero, Coefficients, Input, Number, Rows};
+
+/// Logarithm of the number of rows in the PLONK table used for the well-formedness circuit.
+pub const WELL_FORMEDNESS_K: u32 = 20;
+
+/// Bases of the Miller-Rabin test.
+pub(crate) const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Number of bits of the largest of the [`BASES`].
+const BASE_BITS: usize = 6;
+
+/// Number of bits of all the range-checked values (factors, exponents and modular reductions).
+const RANGE_BITS: usize = MAX_FACTOR_BITS as usize;
+
+/// Number of bits of the power of two in the Miller-Rabin decomposition (at most `RANGE_BITS - 1`).
+const TWO_ADICITY_BITS: usize = 6;
+
+/// Circuit proving that the challenge `n` is a product of two primes with the same, stated number of bits.
+///
+/// There are two instances (public inputs): `n` and `2^(bits-1)`, the smallest number with `bits` bits (see
+/// [`rsa_public_input::encode_well_formedness`]). The advices (private inputs) are the factors together with their
+/// Miller-Rabin certificates.
+#[derive(Clone, Debug)]
+pub struct WellFormednessChallenge {
+    pub(crate) factors: [Value<FactorWitness>; 2],
+}
+
+/// Statement of the well-formedness circuit: `n` is a product of two primes with `factor_bits` bits each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Semiprime {
+    /// The challenge.
+    pub n: u128,
+    /// Number of bits of each of the factors.
+    pub factor_bits: u32,
+}
+
+/// A prime factor together with the witnesses of passing the Miller-Rabin test for each of the [`BASES`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FactorWitness {
+    pub(crate) factor: u64,
+    pub(crate) bases: [BaseWitness; BASES.len()],
+}
+
+/// Witness of passing the Miller-Rabin test for a single base `a`.
+///
+/// With `p - 1 = exponent * 2^two_adicity * (1 + flag)`, either `flag` is not set, `exponent` is odd and
+/// `a^exponent = 1 (mod p)`, or `flag` is set and `a^exponent = -1 (mod p)`.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct BaseWitness {
+    pub(crate) exponent: u64,
+    pub(crate) two_adicity: u32,
+    pub(crate) flag: bool,
+}
+
+impl Default for WellFormednessChallenge {
+    fn default() -> Self {
+        Self {
+            factors: [Value::unknown(), Value::unknown()],
+        }
+    }
+}
+
+impl Circuit<Fr> for WellFormednessChallenge {
+    type Config = <StandardPlonk as Circuit<Fr>>::Config;
+    type FloorPlanner = <StandardPlonk as Circuit<Fr>>::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        StandardPlonk::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "",
+            |mut region| {
+                let mut rows = Rows {
+                    region: &mut region,
+                    config: &config,
+                    offset: 0,
+                };
+
+                // Check that `p*q = n` (`n` is added by the gate from the instance column at this row).
+                let [p, q, _] = rows.assign(
+                    [
+                        Input::Fresh(self.factor(0)),
+                        Input::Fresh(self.factor(1)),
+                        Input::Fresh(Value::known(Fr::zero())),
+                    ],
+                    Coefficients {
+                        ab: -Fr::one(),
+                        ..Default::default()
+                    },
+                )?;
+
+                // Bind the lower bound: `-min + instance = 0`.
+                let lower_bound = rows.region.assign_advice_from_instance(
+                    || "lower bound",
+                    config.instance,
+                    1,
+                    config.a,
+                    1,
+                )?;
+                rows.region.assign_fixed(
+                    || "selector for lower bound",
+                    config.q_a,
+                    1,
+                    || Value::known(-Fr::one()),
+                )?;
+                rows.offset = 2;
+
+                for (factor, witness) in [p, q].iter().zip(&self.factors) {
+                    rows.check_bit_length(factor, &lower_bound)?;
+                    rows.check_primality(factor, witness)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl WellFormednessChallenge {
+    /// Create a circuit with witnesses for the factorization `p * q`.
+    ///
+    /// Returns `None` if any of the factors is not a prime, the factors have different bit lengths or the bit length
+    /// is not between [`MIN_FACTOR_BITS`] and [`MAX_FACTOR_BITS`].
+    pub fn new(p: u128, q: u128) -> Option<Self> {
+        let bits = bit_length(p)?;
+        if bit_length(q)? != bits {
+            return None;
+        }
+        Some(Self {
+            factors: [
+                Value::known(FactorWitness::new(p as u64)?),
+                Value::known(FactorWitness::new(q as u64)?),
+            ],
+        })
+    }
+
+    fn factor(&self, index: usize) -> Value<Fr> {
+        self.factors[index].map(|witness| Fr::from(witness.factor))
+    }
+}
+
+impl FactorWitness {
+    pub(crate) fn new(factor: u64) -> Option<Self> {
+        let mut bases = [BaseWitness::default(); BASES.len()];
+        for (witness, base) in bases.iter_mut().zip(BASES) {
+            *witness = BaseWitness::new(factor, base)?;
+        }
+        Some(Self { factor, bases })
+    }
+}
+
+impl BaseWitness {
+    /// Run the Miller-Rabin test for `p` and `base`. Returns `None` if `p` is found to be composite.
+    fn new(p: u64, base: u64) -> Option<Self> {
+        let two_adicity = (p - 1).trailing_zeros();
+        let odd_part = (p - 1) >> two_adicity;
+
+        let mut power = pow_mod(base, odd_part, p);
+        if power == 1 {
+            return Some(Self {
+                exponent: odd_part,
+                two_adicity,
+                flag: false,
+            });
+        }
+        for squarings in 0..two_adicity {
+            if power == p - 1 {
+                return Some(Self {
+                    exponent: odd_part << squarings,
+                    two_adicity: two_adicity - squarings - 1,
+                    flag: true,
+                });
+            }
+            power = mul_mod(power, power, p);
+        }
+        None
+    }
+}
+
+/// Bit length of `factor`, if it is between [`MIN_FACTOR_BITS`] and [`MAX_FACTOR_BITS`].
+pub(crate) fn bit_length(factor: u128) -> Option<u32> {
+    let bits = u128::BITS - factor.leading_zeros();
+    (MIN_FACTOR_BITS..=MAX_FACTOR_BITS)
+        .contains(&bits)
+        .then_some(bits)
+}
+
+fn pow_mod(base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let (mut base, mut result) = (base % modulus, 1);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn mul_mod(x: u64, y: u64, modulus: u64) -> u64 {
+    (x as u128 * y as u128 % modulus as u128) as u64
+}
+
+/// Checks specific to the well-formedness circuit.
+///
+/// All the values are integers smaller than `2^129` (sums of products of range-checked values), so the gates over `Fr`
+/// never wrap around and express integer relations.
+impl Rows<'_, '_> {
+    /// Compute some `r < 2^RANGE_BITS` such that `x * y = r (mod p)`. The quotient `x * y / p` must fit in
+    /// `quotient_bits`, which is the case whenever `x` is reduced modulo `p` and `y < 2^quotient_bits`.
+    fn mul_mod(
+        &mut self,
+        x: &Number,
+        y: &Number,
+        p: &Number,
+        quotient_bits: usize,
+    ) -> Result<Number, Error> {
+        let product = self.mul(x, y)?;
+        let division = product.value().zip(p.value()).map(|(product, p)| {
+            let (product, p) = (to_u128(*product), to_u128(*p));
+            (
+                product.checked_div(p).unwrap_or_default(),
+                product.checked_rem(p).unwrap_or_default(),
+            )
+        });
+
+        // `quotient * p + remainder = x * y`, with both `quotient` and `remainder` range-checked.
+        let [quotient, _, multiple] = self.assign(
+            [
+                Input::Fresh(division.map(|(quotient, _)| Fr::from_u128(quotient))),
+                Input::Copy(p),
+                Input::Fresh(
+                    division
+                        .zip(p.value())
+                        .map(|((quotient, _), p)| Fr::from_u128(quotient) * p),
+                ),
+            ],
+            Coefficients {
+                c: -Fr::one(),
+                ab: Fr::one(),
+                ..Default::default()
+            },
+        )?;
+        let [_, _, remainder] = self.assign(
+            [
+                Input::Copy(&product),
+                Input::Copy(&multiple),
+                Input::Fresh(division.map(|(_, remainder)| Fr::from_u128(remainder))),
+            ],
+            Coefficients {
+                a: Fr::one(),
+                b: -Fr::one(),
+                c: -Fr::one(),
+                ..Default::default()
+            },
+        )?;
+        self.range_check(quotient_bits, &quotient)?;
+        self.range_check(RANGE_BITS, &remainder)?;
+        Ok(remainder)
+    }
+
+    /// Check that `2^(bits-1) <= factor < 2^bits`, where `lower_bound = 2^(bits-1)`.
+    fn check_bit_length(&mut self, factor: &Number, lower_bound: &Number) -> Result<(), Error> {
+        // `factor - lower_bound >= 0`
+        let above = self.linear((factor, Fr::one()), (lower_bound, -Fr::one()), Fr::zero())?;
+        self.range_check(RANGE_BITS, &above)?;
+        // `2 * lower_bound - 1 - factor >= 0`
+        let below = self.linear((lower_bound, fr(2)), (factor, -Fr::one()), -Fr::one())?;
+        self.range_check(RANGE_BITS, &below)?;
+        Ok(())
+    }
+
+    /// Check that `p` passes the Miller-Rabin test for all the [`BASES`].
+    fn check_primality(&mut self, p: &Number, witness: &Value<FactorWitness>) -> Result<(), Error> {
+        let one = self.constant(Fr::one())?;
+        let p_minus_one = self.linear((p, Fr::one()), (&one, Fr::zero()), -Fr::one())?;
+
+        for (index, base) in BASES.into_iter().enumerate() {
+            let witness = witness.map(|witness| witness.bases[index]);
+
+            // `2^two_adicity`, with `two_adicity` given by its bits.
+            let mut power_of_two = one.clone();
+            for position in 0..TWO_ADICITY_BITS {
+                let bit = self.witness(
+                    witness.map(|witness| Fr::from(((witness.two_adicity >> position) & 1) as u64)),
+                )?;
+                self.assert_boolean(&bit)?;
+                let factor = self.linear(
+                    (&bit, fr((1 << (1 << position)) - 1)),
+                    (&one, Fr::zero()),
+                    Fr::one(),
+                )?;
+                power_of_two = self.mul(&power_of_two, &factor)?;
+            }
+            let flag = self.witness(witness.map(|witness| Fr::from(witness.flag as u64)))?;
+            self.assert_boolean(&flag)?;
+            let flag_factor = self.linear((&flag, Fr::one()), (&one, Fr::zero()), Fr::one())?;
+            let power_of_two = self.mul(&power_of_two, &flag_factor)?;
+
+            // `p - 1 = exponent * 2^two_adicity * (1 + flag)`
+            let exponent = self.witness(witness.map(|witness| Fr::from(witness.exponent)))?;
+            let exponent_bits = self.range_check(RANGE_BITS, &exponent)?;
+            let decomposition = self.mul(&exponent, &power_of_two)?;
+            self.region
+                .constrain_equal(decomposition.cell(), p_minus_one.cell())?;
+
+            // `(1 - flag) * (1 - exponent_lowest_bit) = 0`, i.e. without the flag the exponent is odd.
+            self.assign(
+                [
+                    Input::Copy(&flag),
+                    Input::Copy(exponent_bits.last().unwrap()),
+                    zero(),
+                ],
+                Coefficients {
+                    a: -Fr::one(),
+                    b: -Fr::one(),
+                    ab: Fr::one(),
+                    constant: Fr::one(),
+                    ..Default::default()
+                },
+            )?;
+
+            // `base^exponent (mod p)` with square-and-multiply.
+            let mut power = one.clone();
+            for bit in &exponent_bits {
+                let square = self.mul_mod(&power, &power, p, RANGE_BITS)?;
+                let multiplier =
+                    self.linear((bit, fr(base as i64 - 1)), (&one, Fr::zero()), Fr::one())?;
+                power = self.mul_mod(&square, &multiplier, p, BASE_BITS)?;
+            }
+
+            // `base^exponent = 1 + flag * (p - 2) (mod p)`, i.e. `1` or `-1`.
+            let flag_p = self.mul(&flag, p)?;
+            let expected = self.linear((&flag_p, Fr::one()), (&flag, fr(-2)), Fr::one())?;
+            self.region.constrain_equal(power.cell(), expected.cell())?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert the public input of the well-formedness circuit to a scalar array. Returns `None` if `factor_bits` is not
+/// between [`MIN_FACTOR_BITS`] and [`MAX_FACTOR_BITS`].
+pub fn prepare_public_input(
+    n: u128,
+    factor_bits: u32,
+) -> Option<[Fr; WELL_FORMEDNESS_INSTANCE_COUNT]> {
+    rsa_public_input::encode_well_formedness(n, factor_bits)
+        .map(|elements| elements.map(|element| Fr::from_repr(element).unwrap()))
+}
@@ -7,17 +7,98 @@ mod tests;
 /// is correct, the caller will be rewarded and the contract will terminate.
 ///
 /// Proof verification is outsourced to a chain extension.
+///
+/// Optionally, the deployer can prove at construction time that the challenge is a product of two primes with the
+/// stated bit length (see `new_well_formed`).
+///
+/// A challenge created with `new_hashed` expects proofs of the hashed mode of the circuit, in which the caller and the
+/// deployment are bound through their Poseidon hash (a single instance) instead of separate instances.
+///
+/// A challenge created with `new_private_key` is solved by proving the knowledge of a private exponent for the public
+/// key `(n, e)` instead of the factorization of `n`.
+///
+/// A challenge created with `new_hash_preimage` is solved by proving the knowledge of a preimage of a Poseidon digest.
+///
+/// In general, the contract hosts any puzzle whose public statement it can encode (see `Statement` and
+/// `new_with_statement`), the proof is always bound to the caller and the deployment in the same way.
 #[ink::contract(env = baby_liminal_extension::Environment)]
 pub mod rsa_contract {
     use baby_liminal_extension::KeyHash;
     use ink::prelude::vec::Vec;
+    use rsa_public_input::{Account, ChainId, Domain};
+
+    /// Public statement of the puzzle to solve. Every variant corresponds to a circuit (the off-chain counterpart is
+    /// `rsa_circuit::challenge::ChallengeCircuit`) and determines how the public input is encoded.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum Statement {
+        /// Factorize `n`, with the caller and the deployment as separate instances.
+        Factoring { n: u128 },
+        /// Factorize `n`, with the caller and the deployment bound through their Poseidon hash (see `new_hashed`).
+        HashedFactoring { n: u128 },
+        /// Find a private exponent for the RSA public key `(n, e)` (see `new_private_key`).
+        PrivateKey { n: u128, e: u64 },
+        /// Find a preimage of the Poseidon digest `hash` (see `new_hash_preimage`).
+        HashPreimage { hash: [u8; 32] },
+    }
+
+    impl Statement {
+        /// The RSA modulus of the puzzle, if there is one.
+        fn n(&self) -> Option<u128> {
+            match *self {
+                Statement::Factoring { n }
+                | Statement::HashedFactoring { n }
+                | Statement::PrivateKey { n, .. } => Some(n),
+                Statement::HashPreimage { .. } => None,
+            }
+        }
+
+        /// Encode the statement bound to `caller` and `domain` as expected by the verifier of the corresponding
+        /// circuit. A non-canonical digest has no preimage, so the public input is left empty and every proof is
+        /// rejected.
+        fn encode(&self, caller: &Account, domain: &Domain) -> Vec<u8> {
+            match *self {
+                Statement::Factoring { n } => {
+                    rsa_public_input::encode_bytes(n, caller, domain).to_vec()
+                }
+                Statement::HashedFactoring { n } => {
+                    rsa_public_input::encode_hashed_bytes(n, caller, domain).to_vec()
+                }
+                Statement::PrivateKey { n, e } => {
+                    rsa_public_input::encode_private_key_bytes(n, e, caller, domain).to_vec()
+                }
+                Statement::HashPreimage { hash } => {
+                    rsa_public_input::encode_preimage_bytes(&hash, caller, domain)
+                        .map(|bytes| bytes.to_vec())
+                        .unwrap_or_default()
+                }
+            }
+        }
+    }
 
     #[ink(storage)]
     pub struct RsaContract {
-        /// The number to factorize.
-        n: u128,
+        /// The puzzle to solve.
+        statement: Statement,
         /// Verification key identifier.
         vk_id: Hash,
+        /// Identifier (genesis hash) of the chain the contract is deployed on, as given by the deployer (see `new`).
+        chain_id: ChainId,
+        /// Bit length of both factors, if the deployer proved that `n` is a well-formed semiprime.
+        factor_bits: Option<u32>,
+    }
+
+    /// Reasons for rejecting the deployment of a challenge.
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// The stated bit length of the factors is not supported by the well-formedness circuit.
+        InvalidFactorBits,
+        /// The proof that `n` is a well-formed semiprime was rejected by the verifier.
+        IncorrectWellFormednessProof,
+        /// The digest of a hash-preimage challenge is not a canonical field element, so it has no preimage.
+        NonCanonicalHash,
     }
 
     #[ink(event)]
@@ -32,12 +113,130 @@ pub mod rsa_contract {
         ///
         /// * `n` - The number to factorize.
         /// * `vk_id` - Verification key identifier.
+        /// * `chain_id` - Genesis hash of the chain, which (together with the contract address) every proof is bound to.
+        ///   The contract cannot read it on chain, so it is taken as supplied by the deployer: the binding to the chain
+        ///   is only as good as this value, which participants should check before solving the challenge.
         ///
         /// The transferred tokens, together with the storage deposit will become the reward for the first successful
         /// factorization.
         #[ink(constructor, payable)]
-        pub fn new(n: u128, vk_id: Hash) -> Self {
-            Self { n, vk_id }
+        pub fn new(n: u128, vk_id: Hash, chain_id: ChainId) -> Self {
+            Self::new_with_statement(Statement::Factoring { n }, vk_id, chain_id)
+        }
+
+        /// Creates a new challenge contract for any supported puzzle.
+        ///
+        /// The arguments are the same as for `new`, but `vk_id` must identify the verification key of the circuit
+        /// corresponding to `statement`.
+        #[ink(constructor, payable)]
+        pub fn new_with_statement(statement: Statement, vk_id: Hash, chain_id: ChainId) -> Self {
+            Self {
+                statement,
+                vk_id,
+                chain_id,
+                factor_bits: None,
+            }
+        }
+
+        /// Creates a new RSA challenge contract, which expects proofs of the hashed mode of the circuit: the public
+        /// input is `n` and the Poseidon hash of the caller and the deployment.
+        ///
+        /// The arguments are the same as for `new`, but `vk_id` must identify the verification key of the hashed mode.
+        #[ink(constructor, payable)]
+        pub fn new_hashed(n: u128, vk_id: Hash, chain_id: ChainId) -> Self {
+            Self::new_with_statement(Statement::HashedFactoring { n }, vk_id, chain_id)
+        }
+
+        /// Creates a new RSA challenge contract, which is solved by proving the knowledge of a private exponent for the
+        /// public key `(n, e)`. The public input is `n`, `e`, the caller and the deployment.
+        ///
+        /// Apart from `e`, the arguments are the same as for `new`, but `vk_id` must identify the verification key of
+        /// the private-key circuit.
+        #[ink(constructor, payable)]
+        pub fn new_private_key(n: u128, e: u64, vk_id: Hash, chain_id: ChainId) -> Self {
+            Self::new_with_statement(Statement::PrivateKey { n, e }, vk_id, chain_id)
+        }
+
+        /// Creates a new challenge contract, which is solved by proving the knowledge of a preimage of `hash` under
+        /// Poseidon (see `rsa_public_input::hash_preimage`). The public input is `hash`, the caller and the deployment.
+        /// The deployment fails if `hash` is not a canonical field element (in little-endian bytes).
+        ///
+        /// Apart from `hash`, the arguments are the same as for `new`, but `vk_id` must identify the verification key
+        /// of the hash-preimage circuit.
+        #[ink(constructor, payable)]
+        pub fn new_hash_preimage(
+            hash: [u8; 32],
+            vk_id: Hash,
+            chain_id: ChainId,
+        ) -> Result<Self, Error> {
+            if !rsa_public_input::is_canonical(&hash) {
+                return Err(Error::NonCanonicalHash);
+            }
+            Ok(Self::new_with_statement(
+                Statement::HashPreimage { hash },
+                vk_id,
+                chain_id,
+            ))
+        }
+
+        /// Creates a new RSA challenge contract, for which the deployer proves that `n` is a product of two primes
+        /// with `factor_bits` bits each. The deployment fails if the proof is rejected.
+        ///
+        /// # Arguments
+        ///
+        /// Apart from the arguments of `new`:
+        /// * `factor_bits` - Bit length of both factors.
+        /// * `well_formedness_vk_id` - Identifier of the verification key of the well-formedness circuit.
+        /// * `proof` - Proof that `n` is a well-formed semiprime.
+        #[ink(constructor, payable)]
+        pub fn new_well_formed(
+            n: u128,
+            vk_id: Hash,
+            chain_id: ChainId,
+            factor_bits: u32,
+            well_formedness_vk_id: Hash,
+            proof: Vec<u8>,
+        ) -> Result<Self, Error> {
+            let public_input = rsa_public_input::encode_well_formedness_bytes(n, factor_bits)
+                .ok_or(Error::InvalidFactorBits)?;
+            let well_formedness_vk_id = KeyHash::from_slice(well_formedness_vk_id.as_ref());
+
+            Self::env()
+                .extension()
+                .verify(well_formedness_vk_id, proof, public_input.to_vec())
+                .map_err(|_| Error::IncorrectWellFormednessProof)?;
+
+            Ok(Self {
+                factor_bits: Some(factor_bits),
+                ..Self::new(n, vk_id, chain_id)
+            })
+        }
+
+        /// Returns the number to factorize (or the modulus of the public key), unless the challenge is not about RSA.
+        #[ink(message)]
+        pub fn n(&self) -> Option<u128> {
+            self.statement.n()
+        }
+
+        /// Returns the public exponent, if the challenge is to find a private key.
+        #[ink(message)]
+        pub fn e(&self) -> Option<u64> {
+            match self.statement {
+                Statement::PrivateKey { e, .. } => Some(e),
+                _ => None,
+            }
+        }
+
+        /// Returns the puzzle to solve.
+        #[ink(message)]
+        pub fn statement(&self) -> Statement {
+            self.statement
+        }
+
+        /// Returns the bit length of the factors, if the deployer proved that the challenge is well formed.
+        #[ink(message)]
+        pub fn factor_bits(&self) -> Option<u32> {
+            self.factor_bits
         }
 
         /// Report solution.
@@ -62,25 +261,21 @@ pub mod rsa_contract {
             }
         }
 
-        /// Prepares the public input for the SNARK proof, which includes the number to factorize and the caller's
-        /// address (to prevent front-running attacks).
+        /// Prepares the public input for the SNARK proof, which includes the statement (e.g. the number to factorize),
+        /// the caller's address (to prevent front-running attacks) and this deployment (to prevent replaying proofs
+        /// submitted to other instances of the challenge). In the hashed mode, the latter two are hashed into a single
+        /// element.
         fn prepare_public_input(&self) -> Vec<u8> {
-            let mut input = Vec::new();
-
-            // First input is the number to factorize.
-            input.extend(self.n.to_le_bytes());
-            input.extend([0u8; 16]); // `Fr` elements are 256-bit, so we need to pad the input.
-
-            // The second one is the caller's address. Since it might not be always convertible to `Fr`, we split it
-            // into two 128-bit chunks.
             let caller = self.env().caller();
-            let caller_bytes: &[u8; 32] = caller.as_ref();
-            input.extend(u128::from_le_bytes(caller_bytes[..16].try_into().unwrap()).to_le_bytes());
-            input.extend([0u8; 16]); // `Fr` elements are 256-bit, so we need to pad the input.
-            input.extend(u128::from_le_bytes(caller_bytes[16..].try_into().unwrap()).to_le_bytes());
-            input.extend([0u8; 16]); // `Fr` elements are 256-bit, so we need to pad the input.
-
-            input
+            let caller: &[u8; 32] = caller.as_ref();
+            let caller = Account::new(*caller);
+            let contract = self.env().account_id();
+            let contract: &[u8; 32] = contract.as_ref();
+            let domain = Domain {
+                contract: Account::new(*contract),
+                chain_id: self.chain_id,
+            };
+            self.statement.encode(&caller, &domain)
         }
     }
 }
@@ -2,41 +2,123 @@ use std::error::Error;
 
 use drink::{
     runtime::RuntimeWithContracts,
-    session::{Session, NO_ENDOWMENT, NO_SALT},
+    session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT},
     AccountId32,
 };
 use frame_support::traits::fungible::Inspect;
+use rsa_circuit::{
+    challenge::{ChallengeCircuit, HashPreimage, HashedFactoring, PrivateKey, WellFormedness},
+    private_key::PublicKey,
+    utils::{generate_proof, generate_setup, Account, Domain, MultiOpenScheme},
+    well_formedness::Semiprime,
+    CIRCUIT_MAX_K,
+};
 use runtimes::RuntimeWithAcceptingCE;
 
-use crate::tests::runtimes::RuntimeWithRejectingCE;
+use crate::{
+    rsa_contract::Statement,
+    tests::runtimes::{RuntimeWithRejectingCE, RuntimeWithVerifyingCE},
+};
 
 const CHALLENGE: u128 = 41 * 43;
+/// Challenge with factors big enough for the well-formedness circuit.
+const WELL_FORMED_CHALLENGE: u128 = 101 * 103;
+const FACTOR_BITS: u32 = 7;
 const VK_ID: [u8; 32] = [0; 32];
+const CHAIN_ID: [u8; 32] = [7; 32];
 const REWARD: u128 = 1_000_000_000;
 const PROOF: &[&str] = &["[0, 1, 2, 3]"];
 
 /// The account that will be used as a participant in the tests (the one that submits solutions).
 const PARTICIPANT: AccountId32 = AccountId32::new([2; 32]);
+/// The account that tries to steal the participant's solution.
+const FRONT_RUNNER: AccountId32 = AccountId32::new([3; 32]);
 
 #[drink::contract_bundle_provider]
 enum BundleProvider {}
 
-/// Deploy the contract and return a `drink::Session` object with `PARTICIPANT` set to be the caller.
+/// Deploy the contract and return a `drink::Session` object with `PARTICIPANT` set to be the caller, together with the
+/// domain of the deployment (which the proofs have to be bound to).
 ///
 /// Deployment is done by the Runtime's default account (potentially different from `PARTICIPANT`).
-fn setup<Runtime: RuntimeWithContracts>() -> Result<Session<Runtime>, Box<dyn Error>>
+fn setup<Runtime: RuntimeWithContracts>(
+    vk_id: [u8; 32],
+) -> Result<(Session<Runtime>, Domain), Box<dyn Error>>
 where
     <<Runtime as drink::pallet_contracts::Config>::Currency as Inspect<
         <Runtime as frame_system::Config>::AccountId,
     >>::Balance: From<u128>,
-    <Runtime as frame_system::Config>::AccountId: From<AccountId32>,
+    <Runtime as frame_system::Config>::AccountId: From<AccountId32> + AsRef<[u8; 32]>,
 {
     let mut session = Session::<Runtime>::new()?;
+    let domain = deploy(&mut session, vk_id, NO_SALT)?;
 
-    session.deploy_bundle(
+    session.set_actor(PARTICIPANT.clone().into());
+    Ok((session, domain))
+}
+
+/// Deploy another instance of the contract within `session` (`salt` has to be unique within the session) and return
+/// its domain.
+fn deploy<Runtime: RuntimeWithContracts>(
+    session: &mut Session<Runtime>,
+    vk_id: [u8; 32],
+    salt: Vec<u8>,
+) -> Result<Domain, Box<dyn Error>>
+where
+    <<Runtime as drink::pallet_contracts::Config>::Currency as Inspect<
+        <Runtime as frame_system::Config>::AccountId,
+    >>::Balance: From<u128>,
+    <Runtime as frame_system::Config>::AccountId: AsRef<[u8; 32]>,
+{
+    let address = session.deploy_bundle(
         BundleProvider::local()?,
         "new",
-        &[CHALLENGE.to_string(), format!("{VK_ID:?}")],
+        &[
+            CHALLENGE.to_string(),
+            format!("{vk_id:?}"),
+            format!("{CHAIN_ID:?}"),
+        ],
+        salt,
+        Some(REWARD.into()),
+    )?;
+
+    Ok(Domain {
+        contract: Account::new(*address.as_ref()),
+        chain_id: CHAIN_ID,
+    })
+}
+
+/// Address of the contract deployed as `domain`.
+fn address_of(domain: &Domain) -> AccountId32 {
+    AccountId32::new(domain.contract.as_bytes().try_into().unwrap())
+}
+
+/// Deploy the contract with a proof that `n` is a product of two primes with `factor_bits` bits each and return a
+/// `drink::Session` object with `PARTICIPANT` set to be the caller. Fails if the deployment is rejected.
+fn deploy_well_formed<Runtime: RuntimeWithContracts>(
+    n: u128,
+    factor_bits: u32,
+    well_formedness_vk_id: [u8; 32],
+    proof: &str,
+) -> Result<Session<Runtime>, Box<dyn Error>>
+where
+    <<Runtime as drink::pallet_contracts::Config>::Currency as Inspect<
+        <Runtime as frame_system::Config>::AccountId,
+    >>::Balance: From<u128>,
+    <Runtime as frame_system::Config>::AccountId: From<AccountId32>,
+{
+    let mut session = Session::<Runtime>::new()?;
+    session.deploy_bundle(
+        BundleProvider::local()?,
+        "new_well_formed",
+        &[
+            n.to_string(),
+            format!("{VK_ID:?}"),
+            format!("{CHAIN_ID:?}"),
+            factor_bits.to_string(),
+            format!("{well_formedness_vk_id:?}"),
+            proof.to_string(),
+        ],
         NO_SALT,
         Some(REWARD.into()),
     )?;
@@ -50,7 +132,7 @@ where
 /// - submit a 'correct' proof and get the reward.
 #[drink::test]
 fn report_correct_solution_and_win() -> Result<(), Box<dyn Error>> {
-    let mut session = setup::<RuntimeWithAcceptingCE>()?;
+    let (mut session, _) = setup::<RuntimeWithAcceptingCE>(VK_ID)?;
 
     let winner_balance_before = session.sandbox().free_balance(&PARTICIPANT);
     let _termination_result = session.call::<_, ()>("solve", PROOF, NO_ENDOWMENT);
@@ -66,7 +148,7 @@ fn report_correct_solution_and_win() -> Result<(), Box<dyn Error>> {
 /// - submit an 'incorrect' proof and assert, that the reward wasn't paid
 #[drink::test]
 fn report_incorrect_solution_and_win() -> Result<(), Box<dyn Error>> {
-    let mut session = setup::<RuntimeWithRejectingCE>()?;
+    let (mut session, _) = setup::<RuntimeWithRejectingCE>(VK_ID)?;
 
     let winner_balance_before = session.sandbox().free_balance(&PARTICIPANT);
     session.call::<_, ()>("solve", PROOF, NO_ENDOWMENT)??;
@@ -76,9 +158,399 @@ fn report_incorrect_solution_and_win() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Check that the challenge can be read back from the contract.
+#[drink::test]
+fn read_challenge() -> Result<(), Box<dyn Error>> {
+    let (mut session, _) = setup::<RuntimeWithRejectingCE>(VK_ID)?;
+
+    let n = session.call::<_, Option<u128>>("n", NO_ARGS, NO_ENDOWMENT)??;
+    let factor_bits = session.call::<_, Option<u32>>("factor_bits", NO_ARGS, NO_ENDOWMENT)??;
+
+    assert_eq!(n, Some(CHALLENGE));
+    assert_eq!(factor_bits, None);
+    Ok(())
+}
+
+/// Check that a challenge for any puzzle can be deployed with `new_with_statement` and read back from the contract.
+#[drink::test]
+fn deploy_challenge_with_statement() -> Result<(), Box<dyn Error>> {
+    let mut session = Session::<RuntimeWithRejectingCE>::new()?;
+    session.deploy_bundle(
+        BundleProvider::local()?,
+        "new_with_statement",
+        &[
+            format!("PrivateKey {{ n: {CHALLENGE}, e: 17 }}"),
+            format!("{VK_ID:?}"),
+            format!("{CHAIN_ID:?}"),
+        ],
+        NO_SALT,
+        Some(REWARD.into()),
+    )?;
+
+    let statement = session.call::<_, Statement>("statement", NO_ARGS, NO_ENDOWMENT)??;
+    let n = session.call::<_, Option<u128>>("n", NO_ARGS, NO_ENDOWMENT)??;
+    let e = session.call::<_, Option<u64>>("e", NO_ARGS, NO_ENDOWMENT)??;
+
+    assert_eq!(
+        statement,
+        Statement::PrivateKey {
+            n: CHALLENGE,
+            e: 17
+        }
+    );
+    assert_eq!(n, Some(CHALLENGE));
+    assert_eq!(e, Some(17));
+    Ok(())
+}
+
+/// Check that a challenge with an accepted well-formedness proof is deployed and reports the bit length of its
+/// factors.
+#[drink::test]
+fn deploy_well_formed_challenge() -> Result<(), Box<dyn Error>> {
+    let mut session = deploy_well_formed::<RuntimeWithAcceptingCE>(
+        WELL_FORMED_CHALLENGE,
+        FACTOR_BITS,
+        VK_ID,
+        PROOF[0],
+    )?;
+
+    let factor_bits = session.call::<_, Option<u32>>("factor_bits", NO_ARGS, NO_ENDOWMENT)??;
+
+    assert_eq!(factor_bits, Some(FACTOR_BITS));
+    Ok(())
+}
+
+/// Check that the deployment fails if the well-formedness proof is rejected or the bit length is not supported.
+#[drink::test]
+fn reject_malformed_challenge() {
+    assert!(deploy_well_formed::<RuntimeWithRejectingCE>(
+        WELL_FORMED_CHALLENGE,
+        FACTOR_BITS,
+        VK_ID,
+        PROOF[0]
+    )
+    .is_err());
+    assert!(deploy_well_formed::<RuntimeWithAcceptingCE>(CHALLENGE, 6, VK_ID, PROOF[0]).is_err());
+}
+
+/// Check the well-formedness proof with real verification: the challenge is deployed only for the proven bit length.
+#[drink::test]
+fn deploy_well_formed_challenge_with_real_proof() -> Result<(), Box<dyn Error>> {
+    let snark_setup = WellFormedness::generate_setup(MultiOpenScheme::Gwc);
+    let vk_id = extension_mocks::register_vk(snark_setup.serialize_vk());
+    let semiprime = Semiprime {
+        n: WELL_FORMED_CHALLENGE,
+        factor_bits: FACTOR_BITS,
+    };
+    let proof = WellFormedness::generate_proof(
+        &snark_setup,
+        &semiprime,
+        &(101, 103),
+        Account::default(),
+        Domain::default(),
+    )
+    .unwrap();
+    let proof = format!("{proof:?}");
+
+    assert!(deploy_well_formed::<RuntimeWithVerifyingCE>(
+        WELL_FORMED_CHALLENGE,
+        FACTOR_BITS + 1,
+        vk_id,
+        &proof
+    )
+    .is_err());
+
+    let mut session = deploy_well_formed::<RuntimeWithVerifyingCE>(
+        WELL_FORMED_CHALLENGE,
+        FACTOR_BITS,
+        vk_id,
+        &proof,
+    )?;
+    let factor_bits = session.call::<_, Option<u32>>("factor_bits", NO_ARGS, NO_ENDOWMENT)??;
+    assert_eq!(factor_bits, Some(FACTOR_BITS));
+    Ok(())
+}
+
+/// Simulate a positive scenario with real proof verification, i.e.:
+/// - generate SNARK setup and register the verifying key,
+/// - deploy the contract,
+/// - submit a proof generated for `PARTICIPANT` (and the deployed contract) and get the reward.
+#[drink::test]
+fn report_real_proof_and_win() -> Result<(), Box<dyn Error>> {
+    let snark_setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let vk_id = extension_mocks::register_vk(snark_setup.serialize_vk());
+
+    let (mut session, domain) = setup::<RuntimeWithVerifyingCE>(vk_id)?;
+    let proof = generate_proof(
+        &snark_setup,
+        41,
+        43,
+        Account::new(PARTICIPANT.into()),
+        domain,
+    );
+
+    let winner_balance_before = session.sandbox().free_balance(&PARTICIPANT);
+    let _termination_result = session.call::<_, ()>("solve", &[format!("{proof:?}")], NO_ENDOWMENT);
+    let winner_balance_after = session.sandbox().free_balance(&PARTICIPANT);
+
+    assert!(winner_balance_before + REWARD <= winner_balance_after);
+    Ok(())
+}
+
+/// Simulate a positive scenario in the hashed mode with real proof verification, i.e.:
+/// - deploy the contract with `new_hashed`,
+/// - submit a proof of the raw mode and assert that the reward wasn't paid,
+/// - submit a proof of the hashed mode and get the reward.
+#[drink::test]
+fn report_real_hashed_proof_and_win() -> Result<(), Box<dyn Error>> {
+    let snark_setup = HashedFactoring::generate_setup(MultiOpenScheme::Gwc);
+    let vk_id = extension_mocks::register_vk(snark_setup.serialize_vk());
+
+    let mut session = Session::<RuntimeWithVerifyingCE>::new()?;
+    let address = session.deploy_bundle(
+        BundleProvider::local()?,
+        "new_hashed",
+        &[
+            CHALLENGE.to_string(),
+            format!("{vk_id:?}"),
+            format!("{CHAIN_ID:?}"),
+        ],
+        NO_SALT,
+        Some(REWARD.into()),
+    )?;
+    let domain = Domain {
+        contract: Account::new(*address.as_ref()),
+        chain_id: CHAIN_ID,
+    };
+    session.set_actor(PARTICIPANT.clone().into());
+
+    let raw_setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let raw_proof = generate_proof(&raw_setup, 41, 43, Account::new(PARTICIPANT.into()), domain);
+    let balance_before = session.sandbox().free_balance(&PARTICIPANT);
+    session.call::<_, ()>("solve", &[format!("{raw_proof:?}")], NO_ENDOWMENT)??;
+    let balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert_eq!(balance_before, balance_after);
+
+    let proof = HashedFactoring::generate_proof(
+        &snark_setup,
+        &CHALLENGE,
+        &(41, 43),
+        Account::new(PARTICIPANT.into()),
+        domain,
+    )
+    .unwrap();
+    let _termination_result = session.call::<_, ()>("solve", &[format!("{proof:?}")], NO_ENDOWMENT);
+    let winner_balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert!(balance_after + REWARD <= winner_balance_after);
+    Ok(())
+}
+
+/// Simulate a positive scenario for a private-key challenge with real proof verification, i.e.:
+/// - deploy the contract with `new_private_key`,
+/// - submit a proof of the factorization of `n` and assert that the reward wasn't paid,
+/// - submit a proof of the knowledge of the private exponent and get the reward.
+#[drink::test]
+fn report_real_private_key_proof_and_win() -> Result<(), Box<dyn Error>> {
+    let (p, q, e, d) = (61, 53, 17, 2753);
+    let snark_setup = PrivateKey::generate_setup(MultiOpenScheme::Gwc);
+    let vk_id = extension_mocks::register_vk(snark_setup.serialize_vk());
+
+    let mut session = Session::<RuntimeWithVerifyingCE>::new()?;
+    let address = session.deploy_bundle(
+        BundleProvider::local()?,
+        "new_private_key",
+        &[
+            (p * q).to_string(),
+            e.to_string(),
+            format!("{vk_id:?}"),
+            format!("{CHAIN_ID:?}"),
+        ],
+        NO_SALT,
+        Some(REWARD.into()),
+    )?;
+    let domain = Domain {
+        contract: Account::new(*address.as_ref()),
+        chain_id: CHAIN_ID,
+    };
+    session.set_actor(PARTICIPANT.clone().into());
+    assert_eq!(
+        session.call::<_, Option<u64>>("e", NO_ARGS, NO_ENDOWMENT)??,
+        Some(e)
+    );
+
+    let raw_setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let raw_proof = generate_proof(&raw_setup, p, q, Account::new(PARTICIPANT.into()), domain);
+    let balance_before = session.sandbox().free_balance(&PARTICIPANT);
+    session.call::<_, ()>("solve", &[format!("{raw_proof:?}")], NO_ENDOWMENT)??;
+    let balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert_eq!(balance_before, balance_after);
+
+    let proof = PrivateKey::generate_proof(
+        &snark_setup,
+        &PublicKey { n: p * q, e },
+        &(p, q, d),
+        Account::new(PARTICIPANT.into()),
+        domain,
+    )
+    .unwrap();
+    let _termination_result = session.call::<_, ()>("solve", &[format!("{proof:?}")], NO_ENDOWMENT);
+    let winner_balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert!(balance_after + REWARD <= winner_balance_after);
+    Ok(())
+}
+
+/// Simulate a positive scenario for a hash-preimage challenge with real proof verification, i.e.:
+/// - deploy the contract with `new_hash_preimage`,
+/// - submit a proof for another preimage and assert that the reward wasn't paid,
+/// - submit a proof for the right preimage and get the reward.
+#[drink::test]
+fn report_real_preimage_proof_and_win() -> Result<(), Box<dyn Error>> {
+    // Field elements are encoded in little-endian bytes.
+    let (mut preimage, mut wrong_preimage) = ([0u8; 32], [0u8; 32]);
+    preimage[0] = 1;
+    wrong_preimage[0] = 2;
+    let hash = rsa_public_input::hash_preimage(&preimage).unwrap();
+    let snark_setup = HashPreimage::generate_setup(MultiOpenScheme::Gwc);
+    let vk_id = extension_mocks::register_vk(snark_setup.serialize_vk());
+
+    let mut session = Session::<RuntimeWithVerifyingCE>::new()?;
+    let address = session.deploy_bundle(
+        BundleProvider::local()?,
+        "new_hash_preimage",
+        &[
+            format!("{hash:?}"),
+            format!("{vk_id:?}"),
+            format!("{CHAIN_ID:?}"),
+        ],
+        NO_SALT,
+        Some(REWARD.into()),
+    )?;
+    let domain = Domain {
+        contract: Account::new(*address.as_ref()),
+        chain_id: CHAIN_ID,
+    };
+    session.set_actor(PARTICIPANT.clone().into());
+    assert_eq!(
+        session.call::<_, Option<u128>>("n", NO_ARGS, NO_ENDOWMENT)??,
+        None
+    );
+
+    let account = Account::new(PARTICIPANT.into());
+    let wrong_hash = rsa_public_input::hash_preimage(&wrong_preimage).unwrap();
+    let wrong_proof =
+        HashPreimage::generate_proof(&snark_setup, &wrong_hash, &wrong_preimage, account, domain)
+            .unwrap();
+    let balance_before = session.sandbox().free_balance(&PARTICIPANT);
+    session.call::<_, ()>("solve", &[format!("{wrong_proof:?}")], NO_ENDOWMENT)??;
+    let balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert_eq!(balance_before, balance_after);
+
+    let proof =
+        HashPreimage::generate_proof(&snark_setup, &hash, &preimage, account, domain).unwrap();
+    let _termination_result = session.call::<_, ()>("solve", &[format!("{proof:?}")], NO_ENDOWMENT);
+    let winner_balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert!(balance_after + REWARD <= winner_balance_after);
+    Ok(())
+}
+
+/// Check that a hash-preimage challenge with a digest outside of the field cannot be deployed.
+#[drink::test]
+fn reject_non_canonical_hash() -> Result<(), Box<dyn Error>> {
+    let mut session = Session::<RuntimeWithAcceptingCE>::new()?;
+    let deployment = session.deploy_bundle(
+        BundleProvider::local()?,
+        "new_hash_preimage",
+        &[
+            format!("{:?}", [0xffu8; 32]),
+            format!("{VK_ID:?}"),
+            format!("{CHAIN_ID:?}"),
+        ],
+        NO_SALT,
+        Some(REWARD.into()),
+    );
+    assert!(deployment.is_err());
+    Ok(())
+}
+
+/// Simulate a front-running attempt with real proof verification, i.e.:
+/// - deploy the contract,
+/// - submit a proof generated for `PARTICIPANT` from another account and assert that the reward wasn't paid,
+/// - submit the same proof from `PARTICIPANT` and get the reward.
+#[drink::test]
+fn front_runner_cannot_steal_real_proof() -> Result<(), Box<dyn Error>> {
+    let snark_setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let vk_id = extension_mocks::register_vk(snark_setup.serialize_vk());
+
+    let (mut session, domain) = setup::<RuntimeWithVerifyingCE>(vk_id)?;
+    let proof = generate_proof(
+        &snark_setup,
+        41,
+        43,
+        Account::new(PARTICIPANT.into()),
+        domain,
+    );
+
+    session.set_actor(FRONT_RUNNER.clone().into());
+    let front_runner_balance_before = session.sandbox().free_balance(&FRONT_RUNNER);
+    session.call::<_, ()>("solve", &[format!("{proof:?}")], NO_ENDOWMENT)??;
+    let front_runner_balance_after = session.sandbox().free_balance(&FRONT_RUNNER);
+    assert_eq!(front_runner_balance_before, front_runner_balance_after);
+
+    session.set_actor(PARTICIPANT.clone().into());
+    let winner_balance_before = session.sandbox().free_balance(&PARTICIPANT);
+    let _termination_result = session.call::<_, ()>("solve", &[format!("{proof:?}")], NO_ENDOWMENT);
+    let winner_balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert!(winner_balance_before + REWARD <= winner_balance_after);
+    Ok(())
+}
+
+/// Simulate a replay attempt with real proof verification, i.e.:
+/// - deploy two instances of the contract with the same challenge,
+/// - submit a proof generated for the first one to the second one and assert that the reward wasn't paid,
+/// - submit the same proof to the first one and get the reward.
+#[drink::test]
+fn proof_cannot_be_replayed_against_another_deployment() -> Result<(), Box<dyn Error>> {
+    let snark_setup = generate_setup(CIRCUIT_MAX_K, MultiOpenScheme::Gwc);
+    let vk_id = extension_mocks::register_vk(snark_setup.serialize_vk());
+
+    let mut session = Session::<RuntimeWithVerifyingCE>::new()?;
+    let domain = deploy(&mut session, vk_id, NO_SALT)?;
+    let other_domain = deploy(&mut session, vk_id, vec![1])?;
+    assert_ne!(domain, other_domain);
+    session.set_actor(PARTICIPANT.clone().into());
+    let proof = generate_proof(
+        &snark_setup,
+        41,
+        43,
+        Account::new(PARTICIPANT.into()),
+        domain,
+    );
+
+    let balance_before = session.sandbox().free_balance(&PARTICIPANT);
+    session.call_with_address::<_, ()>(
+        address_of(&other_domain),
+        "solve",
+        &[format!("{proof:?}")],
+        NO_ENDOWMENT,
+    )??;
+    let balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert_eq!(balance_before, balance_after);
+
+    let _termination_result = session.call_with_address::<_, ()>(
+        address_of(&domain),
+        "solve",
+        &[format!("{proof:?}")],
+        NO_ENDOWMENT,
+    );
+    let winner_balance_after = session.sandbox().free_balance(&PARTICIPANT);
+    assert!(balance_after + REWARD <= winner_balance_after);
+    Ok(())
+}
+
 mod runtimes {
     pub use accepting_runtime::RuntimeWithAcceptingCE;
     pub use rejecting_runtime::RuntimeWithRejectingCE;
+    pub use verifying_runtime::RuntimeWithVerifyingCE;
 
     mod accepting_runtime {
         drink::create_minimal_runtime!(
@@ -92,9 +564,17 @@ mod runtimes {
             crate::tests::extension_mocks::AlwaysRejectExtension
         );
     }
+    mod verifying_runtime {
+        drink::create_minimal_runtime!(
+            RuntimeWithVerifyingCE,
+            crate::tests::extension_mocks::VerifyingExtension
+        );
+    }
 }
 
 mod extension_mocks {
+    use std::{collections::BTreeMap, sync::Mutex};
+
     use baby_liminal_extension::{
         extension_ids::{EXTENSION_ID, VERIFY_FUNC_ID},
         status_codes::{VERIFY_INCORRECT_PROOF, VERIFY_SUCCESS},
@@ -102,6 +582,18 @@ mod extension_mocks {
     use drink::pallet_contracts::chain_extension::{
         ChainExtension, Config as ContractsConfig, Environment, Ext, InitState, Result, RetVal,
     };
+    use frame_support::{Blake2_256, StorageHasher};
+    use rsa_circuit::utils::verify_serialized;
+
+    /// Verifying keys known to `VerifyingExtension`, identified by their hashes.
+    static VERIFYING_KEYS: Mutex<BTreeMap<[u8; 32], Vec<u8>>> = Mutex::new(BTreeMap::new());
+
+    /// Register a serialized verifying key for `VerifyingExtension` and return its identifier.
+    pub fn register_vk(vk: Vec<u8>) -> [u8; 32] {
+        let vk_id = Blake2_256::hash(&vk);
+        VERIFYING_KEYS.lock().unwrap().insert(vk_id, vk);
+        vk_id
+    }
 
     /// A chain extension that will always claim that the SNARK proof is correct.
     #[derive(Default)]
@@ -124,4 +616,28 @@ mod extension_mocks {
             Ok(RetVal::Converging(VERIFY_INCORRECT_PROOF))
         }
     }
+
+    /// A chain extension that actually verifies the SNARK proof against a verifying key registered with `register_vk`.
+    ///
+    /// Every failure (including an unknown verifying key) is reported as an incorrect proof.
+    #[derive(Default)]
+    pub struct VerifyingExtension;
+
+    impl<Runtime: ContractsConfig> ChainExtension<Runtime> for VerifyingExtension {
+        fn call<E: Ext<T = Runtime>>(&mut self, env: Environment<E, InitState>) -> Result<RetVal> {
+            assert!(env.ext_id() == EXTENSION_ID && env.func_id() == VERIFY_FUNC_ID);
+
+            let mut env = env.buf_in_buf_out();
+            let (vk_id, proof, public_input): ([u8; 32], Vec<u8>, Vec<u8>) =
+                env.read_as_unbounded(env.in_len())?;
+
+            let vk = VERIFYING_KEYS.lock().unwrap().get(&vk_id).cloned();
+            match vk {
+                Some(vk) if verify_serialized(&vk, &proof, &public_input) => {
+                    Ok(RetVal::Converging(VERIFY_SUCCESS))
+                }
+                _ => Ok(RetVal::Converging(VERIFY_INCORRECT_PROOF)),
+            }
+        }
+    }
 }
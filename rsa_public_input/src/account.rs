@@ -0,0 +1,102 @@
+//! Identifiers of participants (and contracts) in the formats used by different chains.
+
+use crate::{encode_u128, FieldElementBytes};
+
+/// Maximal length of an account identifier in bytes.
+pub const MAX_ACCOUNT_LENGTH: usize = 32;
+
+/// Length of an EVM (`H160`) address in bytes.
+pub const H160_LENGTH: usize = 20;
+
+/// Identifier of the participant (or the address of a contract), e.g. a 32-byte Substrate `AccountId32` or a 20-byte
+/// EVM `H160` address. Any other identifier of at most [`MAX_ACCOUNT_LENGTH`] bytes is supported as well.
+///
+/// Every account is encoded as two field elements (see [`Account::encode`]). Accounts of different lengths never share
+/// an encoding, so a proof bound to an account in one format cannot be reused for an account in another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Account {
+    /// The identifier, padded with zeros.
+    bytes: [u8; MAX_ACCOUNT_LENGTH],
+    /// Length of the identifier in bytes.
+    length: u8,
+}
+
+impl Account {
+    /// Create a 32-byte account.
+    pub const fn new(bytes: [u8; MAX_ACCOUNT_LENGTH]) -> Self {
+        Self {
+            bytes,
+            length: MAX_ACCOUNT_LENGTH as u8,
+        }
+    }
+
+    /// Create an account from a 20-byte EVM (`H160`) address.
+    pub const fn from_h160(address: [u8; H160_LENGTH]) -> Self {
+        let mut bytes = [0u8; MAX_ACCOUNT_LENGTH];
+        let mut i = 0;
+        while i < H160_LENGTH {
+            bytes[i] = address[i];
+            i += 1;
+        }
+        Self {
+            bytes,
+            length: H160_LENGTH as u8,
+        }
+    }
+
+    /// Create an account from an identifier of arbitrary length. Returns `None` if it is longer than
+    /// [`MAX_ACCOUNT_LENGTH`].
+    pub fn from_slice(identifier: &[u8]) -> Option<Self> {
+        if identifier.len() > MAX_ACCOUNT_LENGTH {
+            return None;
+        }
+        let mut bytes = [0u8; MAX_ACCOUNT_LENGTH];
+        bytes[..identifier.len()].copy_from_slice(identifier);
+        Some(Self {
+            bytes,
+            length: identifier.len() as u8,
+        })
+    }
+
+    /// The identifier (without padding).
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.length as usize]
+    }
+
+    /// Encode the account as two field elements.
+    ///
+    /// A 32-byte account is split into two 128-bit halves (lower first). This is also how the other formats are laid
+    /// out (after padding them with zeros to 32 bytes), but then the length of the identifier is additionally stored
+    /// in the upper element, right above its 128 bits (preceded by a marker byte, so that the empty identifier is
+    /// distinguishable from the zero account). Since the upper half of a 32-byte account is always smaller than
+    /// `2^128`, the encoding is injective. All elements are smaller than `2^144`, so they are canonical.
+    pub fn encode(&self) -> [FieldElementBytes; 2] {
+        let (low, high) = self.bytes.split_at(16);
+        let low = encode_u128(u128::from_le_bytes(low.try_into().unwrap()));
+        let mut high = encode_u128(u128::from_le_bytes(high.try_into().unwrap()));
+        if self.length as usize != MAX_ACCOUNT_LENGTH {
+            high[16] = 1;
+            high[17] = self.length;
+        }
+        [low, high]
+    }
+}
+
+impl Default for Account {
+    /// The 32-byte account consisting of zeros.
+    fn default() -> Self {
+        Self::new([0u8; MAX_ACCOUNT_LENGTH])
+    }
+}
+
+impl From<[u8; MAX_ACCOUNT_LENGTH]> for Account {
+    fn from(bytes: [u8; MAX_ACCOUNT_LENGTH]) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<[u8; H160_LENGTH]> for Account {
+    fn from(address: [u8; H160_LENGTH]) -> Self {
+        Self::from_h160(address)
+    }
+}
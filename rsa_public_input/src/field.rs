@@ -0,0 +1,158 @@
+//! Minimal arithmetic of the BN254 scalar field, sufficient for hashing.
+//!
+//! Elements are kept in the Montgomery form (with `R = 2^256`) on four little-endian 64-bit limbs. Only the operations
+//! needed by Poseidon are implemented and none of them is constant-time (nothing secret is processed here).
+
+use core::ops::{Add, Mul};
+
+use crate::{is_canonical, FieldElementBytes, FIELD_ELEMENT_SIZE};
+
+/// `r` on little-endian limbs.
+const MODULUS: [u64; 4] = [
+    0x43e1f593f0000001,
+    0x2833e84879b97091,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+];
+
+/// `-r^-1 mod 2^64`.
+const MODULUS_NEG_INV: u64 = 0xc2e1f593efffffff;
+
+/// `R^2 mod r`.
+const R2: Scalar = Scalar([
+    0x1bb8e645ae216da7,
+    0x53fe3ab1e35c59e3,
+    0x8c49833d53bb8085,
+    0x0216d0b17f4e44a5,
+]);
+
+/// Element of the BN254 scalar field (`Fr`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Scalar([u64; 4]);
+
+impl Scalar {
+    /// The additive identity.
+    pub const ZERO: Scalar = Scalar([0; 4]);
+
+    /// Create an element from limbs that are already in the Montgomery form.
+    pub(crate) const fn from_montgomery(limbs: [u64; 4]) -> Self {
+        Scalar(limbs)
+    }
+
+    /// Decode an element from its little-endian representation. Returns `None` if `bytes` is not canonical.
+    pub fn from_bytes(bytes: &FieldElementBytes) -> Option<Self> {
+        if !is_canonical(bytes) {
+            return None;
+        }
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Some(Scalar(limbs) * R2)
+    }
+
+    /// Encode the element as little-endian bytes (the same representation as `Fr::to_repr`).
+    pub fn to_bytes(self) -> FieldElementBytes {
+        let canonical = montgomery_reduce([self.0[0], self.0[1], self.0[2], self.0[3], 0, 0, 0, 0]);
+        let mut bytes = [0u8; FIELD_ELEMENT_SIZE];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(canonical.0) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Embed a 128-bit integer into the field.
+    pub fn from_u128(value: u128) -> Self {
+        Scalar([value as u64, (value >> 64) as u64, 0, 0]) * R2
+    }
+
+    /// Raise the element to the fifth power (the Poseidon S-box).
+    pub fn pow5(self) -> Self {
+        let square = self * self;
+        square * square * self
+    }
+}
+
+impl Add for Scalar {
+    type Output = Scalar;
+
+    fn add(self, other: Scalar) -> Scalar {
+        let mut sum = [0u64; 4];
+        let mut carry = 0;
+        for (limb, (a, b)) in sum.iter_mut().zip(self.0.iter().zip(other.0)) {
+            (*limb, carry) = adc(*a, b, carry);
+        }
+        // Both summands are smaller than `r < 2^254`, so the sum fits into four limbs.
+        subtract_modulus_if_needed(sum)
+    }
+}
+
+impl Mul for Scalar {
+    type Output = Scalar;
+
+    fn mul(self, other: Scalar) -> Scalar {
+        let mut product = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0;
+            for j in 0..4 {
+                (product[i + j], carry) = mac(product[i + j], self.0[i], other.0[j], carry);
+            }
+            product[i + 4] = carry;
+        }
+        montgomery_reduce(product)
+    }
+}
+
+/// Compute `a + b + carry`, returning the result and the new carry.
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let sum = a as u128 + b as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+/// Compute `a - b - borrow`, returning the result and the new borrow (0 or 1).
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let difference = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (difference as u64, (difference >> 127) as u64)
+}
+
+/// Compute `a + b * c + carry`, returning the result and the new carry.
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let sum = a as u128 + (b as u128) * (c as u128) + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+/// Montgomery reduction of a 512-bit number smaller than `r * R`.
+fn montgomery_reduce(mut t: [u64; 8]) -> Scalar {
+    let mut carry_out = 0;
+    for i in 0..4 {
+        let m = t[i].wrapping_mul(MODULUS_NEG_INV);
+        let mut carry = 0;
+        for j in 0..4 {
+            (t[i + j], carry) = mac(t[i + j], m, MODULUS[j], carry);
+        }
+        for limb in t.iter_mut().skip(i + 4) {
+            if carry == 0 {
+                break;
+            }
+            (*limb, carry) = adc(*limb, 0, carry);
+        }
+        carry_out += carry;
+    }
+    // The result is smaller than `2r < 2^256`, so there is no carry out of the top limb.
+    debug_assert_eq!(carry_out, 0);
+    subtract_modulus_if_needed([t[4], t[5], t[6], t[7]])
+}
+
+/// Reduce a number smaller than `2r` to the range `[0, r)`.
+fn subtract_modulus_if_needed(value: [u64; 4]) -> Scalar {
+    let mut difference = [0u64; 4];
+    let mut borrow = 0;
+    for (limb, (a, b)) in difference.iter_mut().zip(value.iter().zip(MODULUS)) {
+        (*limb, borrow) = sbb(*a, b, borrow);
+    }
+    if borrow == 0 {
+        Scalar(difference)
+    } else {
+        Scalar(value)
+    }
+}
@@ -0,0 +1,289 @@
+//! Encoding of the RSA challenge public input.
+//!
+//! Both the host (when generating proofs) and the contract (when verifying them through the chain extension) have to
+//! agree on how `n`, the participant's account and the deployment the proof is bound to are laid out as field
+//! elements. This crate is the single source of truth for that layout. It is `no_std`, so that it can be used from
+//! within the contract.
+//!
+//! In the hashed mode (see [`encode_hashed`]), the account and the deployment are not passed as separate instances,
+//! but are hashed (with Poseidon) into a single one, which the circuit recomputes from private inputs.
+//!
+//! The same holds for the public input of the companion circuit, in which the deployer proves that the challenge is
+//! well formed (see [`encode_well_formedness`]), of the private-key challenge, in which the public exponent `e` is
+//! bound as well (see [`encode_private_key`]), and of the hash-preimage challenge, in which the digest takes the place
+//! of `n` (see [`encode_preimage`]).
+//!
+//! Apart from that, the crate provides a (likewise `no_std`) Poseidon hash over the same field, for the parts of the
+//! protocol that have to be recomputed both on the host and in the contract.
+
+#![cfg_attr(not(test), no_std)]
+#![deny(missing_docs)]
+
+mod account;
+pub mod field;
+pub mod poseidon;
+#[cfg(test)]
+mod tests;
+
+pub use account::{Account, H160_LENGTH, MAX_ACCOUNT_LENGTH};
+use field::Scalar;
+
+/// Size of a serialized scalar field element (`Fr`) in bytes.
+pub const FIELD_ELEMENT_SIZE: usize = 32;
+
+/// Number of public inputs (instances) of the circuit: `n`, followed by the lower and upper half of the account, the
+/// contract address and the chain id.
+pub const INSTANCE_COUNT: usize = 7;
+
+/// Number of public inputs of the circuit in the hashed mode: `n` and the hash of the context.
+pub const HASHED_INSTANCE_COUNT: usize = 2;
+
+/// Number of field elements of the context (the account and the deployment) bound to a proof: the lower and upper
+/// halves of the account, the contract address and the chain id.
+pub const CONTEXT_ELEMENT_COUNT: usize = INSTANCE_COUNT - 1;
+
+/// Number of public inputs of the private-key circuit: `n`, the public exponent `e` and the context.
+pub const PRIVATE_KEY_INSTANCE_COUNT: usize = CONTEXT_ELEMENT_COUNT + 2;
+
+/// Number of public inputs of the hash-preimage circuit: the digest and the context.
+pub const PREIMAGE_INSTANCE_COUNT: usize = CONTEXT_ELEMENT_COUNT + 1;
+
+/// Number of public inputs of the well-formedness circuit: `n` and the lower bound of both factors.
+pub const WELL_FORMEDNESS_INSTANCE_COUNT: usize = 2;
+
+/// Minimal bit length of the factors of a challenge proven to be well formed.
+pub const MIN_FACTOR_BITS: u32 = 7;
+
+/// Maximal bit length of the factors of a challenge proven to be well formed.
+pub const MAX_FACTOR_BITS: u32 = 64;
+
+/// Modulus of the BN254 scalar field (`r`) in little-endian bytes.
+pub const MODULUS: FieldElementBytes = [
+    0x01, 0x00, 0x00, 0xf0, 0x93, 0xf5, 0xe1, 0x43, 0x91, 0x70, 0xb9, 0x79, 0x48, 0xe8, 0x33, 0x28,
+    0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e, 0x64, 0x30,
+];
+
+/// Type representing an identifier of the chain (its genesis hash).
+pub type ChainId = [u8; 32];
+
+/// Deployment of the challenge that a proof is bound to.
+///
+/// Including it in the public input prevents replaying a proof against another contract with the same `n`, or against
+/// the same contract address on another chain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Domain {
+    /// Address of the challenge contract.
+    pub contract: Account,
+    /// Identifier of the chain the contract is deployed on.
+    pub chain_id: ChainId,
+}
+
+/// Little-endian representation of a scalar field element.
+pub type FieldElementBytes = [u8; FIELD_ELEMENT_SIZE];
+
+/// Encode `n`, `account` and `domain` as field elements.
+///
+/// Since `account`, the contract address and the chain id are (up to) 256 bits long, they might not fit into a single
+/// field element, so we split each of them into two 128-bit chunks (see [`Account::encode`] for accounts that are
+/// not 32 bytes long). Every element is therefore smaller than `2^144`, which guarantees that the encoding is
+/// canonical.
+pub fn encode(n: u128, account: &Account, domain: &Domain) -> [FieldElementBytes; INSTANCE_COUNT] {
+    let [account_low, account_high] = account.encode();
+    let [contract_low, contract_high] = domain.contract.encode();
+    let [chain_low, chain_high] = encode_halves(&domain.chain_id);
+    [
+        encode_u128(n),
+        account_low,
+        account_high,
+        contract_low,
+        contract_high,
+        chain_low,
+        chain_high,
+    ]
+}
+
+/// Encode `n`, `account` and `domain` as concatenated field elements, as expected by the on-chain verifier.
+pub fn encode_bytes(
+    n: u128,
+    account: &Account,
+    domain: &Domain,
+) -> [u8; INSTANCE_COUNT * FIELD_ELEMENT_SIZE] {
+    let mut bytes = [0u8; INSTANCE_COUNT * FIELD_ELEMENT_SIZE];
+    for (chunk, element) in bytes
+        .chunks_exact_mut(FIELD_ELEMENT_SIZE)
+        .zip(encode(n, account, domain))
+    {
+        chunk.copy_from_slice(&element);
+    }
+    bytes
+}
+
+/// Encode the context bound to a proof (`account` and `domain`) as field elements, in the same order as in [`encode`].
+pub fn encode_context(
+    account: &Account,
+    domain: &Domain,
+) -> [FieldElementBytes; CONTEXT_ELEMENT_COUNT] {
+    let [_, context @ ..] = encode(0, account, domain);
+    context
+}
+
+/// Hash the context (`account` and `domain`) with [`poseidon::hash`].
+pub fn hash_context(account: &Account, domain: &Domain) -> FieldElementBytes {
+    let elements = encode_context(account, domain).map(|element| {
+        Scalar::from_bytes(&element).expect("Context elements are always canonical")
+    });
+    poseidon::hash(&elements).to_bytes()
+}
+
+/// Encode `n` and the hash of `account` and `domain` as field elements (the hashed mode).
+///
+/// Instead of exposing every context element as a separate instance, the circuit absorbs them into a Poseidon hash, so
+/// binding a proof to more context doesn't increase the number of instances.
+pub fn encode_hashed(
+    n: u128,
+    account: &Account,
+    domain: &Domain,
+) -> [FieldElementBytes; HASHED_INSTANCE_COUNT] {
+    [encode_u128(n), hash_context(account, domain)]
+}
+
+/// Encode the public input of the hashed mode as concatenated field elements, as expected by the on-chain verifier.
+pub fn encode_hashed_bytes(
+    n: u128,
+    account: &Account,
+    domain: &Domain,
+) -> [u8; HASHED_INSTANCE_COUNT * FIELD_ELEMENT_SIZE] {
+    let mut bytes = [0u8; HASHED_INSTANCE_COUNT * FIELD_ELEMENT_SIZE];
+    for (chunk, element) in bytes
+        .chunks_exact_mut(FIELD_ELEMENT_SIZE)
+        .zip(encode_hashed(n, account, domain))
+    {
+        chunk.copy_from_slice(&element);
+    }
+    bytes
+}
+
+/// Encode `n`, the public exponent `e`, `account` and `domain` as field elements (the public input of the private-key
+/// challenge). The context is laid out as in [`encode`], after `e`.
+pub fn encode_private_key(
+    n: u128,
+    e: u64,
+    account: &Account,
+    domain: &Domain,
+) -> [FieldElementBytes; PRIVATE_KEY_INSTANCE_COUNT] {
+    let mut elements = [[0u8; FIELD_ELEMENT_SIZE]; PRIVATE_KEY_INSTANCE_COUNT];
+    elements[0] = encode_u128(n);
+    elements[1] = encode_u128(e as u128);
+    elements[2..].copy_from_slice(&encode_context(account, domain));
+    elements
+}
+
+/// Encode the public input of the private-key challenge as concatenated field elements, as expected by the on-chain
+/// verifier.
+pub fn encode_private_key_bytes(
+    n: u128,
+    e: u64,
+    account: &Account,
+    domain: &Domain,
+) -> [u8; PRIVATE_KEY_INSTANCE_COUNT * FIELD_ELEMENT_SIZE] {
+    let mut bytes = [0u8; PRIVATE_KEY_INSTANCE_COUNT * FIELD_ELEMENT_SIZE];
+    for (chunk, element) in bytes
+        .chunks_exact_mut(FIELD_ELEMENT_SIZE)
+        .zip(encode_private_key(n, e, account, domain))
+    {
+        chunk.copy_from_slice(&element);
+    }
+    bytes
+}
+
+/// Compute the digest of `preimage` for the hash-preimage challenge, i.e. its [`poseidon::hash`] (as a single element).
+///
+/// Returns `None` if `preimage` is not canonical.
+pub fn hash_preimage(preimage: &FieldElementBytes) -> Option<FieldElementBytes> {
+    Some(poseidon::hash(&[Scalar::from_bytes(preimage)?]).to_bytes())
+}
+
+/// Encode the digest `hash`, `account` and `domain` as field elements (the public input of the hash-preimage
+/// challenge). The context is laid out as in [`encode`], after the digest.
+///
+/// Returns `None` if `hash` is not canonical (such a digest has no preimage).
+pub fn encode_preimage(
+    hash: &FieldElementBytes,
+    account: &Account,
+    domain: &Domain,
+) -> Option<[FieldElementBytes; PREIMAGE_INSTANCE_COUNT]> {
+    if !is_canonical(hash) {
+        return None;
+    }
+    let mut elements = [[0u8; FIELD_ELEMENT_SIZE]; PREIMAGE_INSTANCE_COUNT];
+    elements[0] = *hash;
+    elements[1..].copy_from_slice(&encode_context(account, domain));
+    Some(elements)
+}
+
+/// Encode the public input of the hash-preimage challenge as concatenated field elements, as expected by the on-chain
+/// verifier.
+pub fn encode_preimage_bytes(
+    hash: &FieldElementBytes,
+    account: &Account,
+    domain: &Domain,
+) -> Option<[u8; PREIMAGE_INSTANCE_COUNT * FIELD_ELEMENT_SIZE]> {
+    let mut bytes = [0u8; PREIMAGE_INSTANCE_COUNT * FIELD_ELEMENT_SIZE];
+    for (chunk, element) in bytes
+        .chunks_exact_mut(FIELD_ELEMENT_SIZE)
+        .zip(encode_preimage(hash, account, domain)?)
+    {
+        chunk.copy_from_slice(&element);
+    }
+    Some(bytes)
+}
+
+/// Encode the public input of the well-formedness circuit: `n` and `2^(factor_bits - 1)`, the smallest number with
+/// `factor_bits` bits.
+///
+/// Returns `None` if `factor_bits` is not between [`MIN_FACTOR_BITS`] and [`MAX_FACTOR_BITS`].
+pub fn encode_well_formedness(
+    n: u128,
+    factor_bits: u32,
+) -> Option<[FieldElementBytes; WELL_FORMEDNESS_INSTANCE_COUNT]> {
+    if !(MIN_FACTOR_BITS..=MAX_FACTOR_BITS).contains(&factor_bits) {
+        return None;
+    }
+    Some([encode_u128(n), encode_u128(1 << (factor_bits - 1))])
+}
+
+/// Encode the public input of the well-formedness circuit as concatenated field elements, as expected by the on-chain
+/// verifier.
+pub fn encode_well_formedness_bytes(
+    n: u128,
+    factor_bits: u32,
+) -> Option<[u8; WELL_FORMEDNESS_INSTANCE_COUNT * FIELD_ELEMENT_SIZE]> {
+    let mut bytes = [0u8; WELL_FORMEDNESS_INSTANCE_COUNT * FIELD_ELEMENT_SIZE];
+    for (chunk, element) in bytes
+        .chunks_exact_mut(FIELD_ELEMENT_SIZE)
+        .zip(encode_well_formedness(n, factor_bits)?)
+    {
+        chunk.copy_from_slice(&element);
+    }
+    Some(bytes)
+}
+
+/// Check whether `element` is a canonical encoding of a field element, i.e. whether it is smaller than `r`.
+pub fn is_canonical(element: &FieldElementBytes) -> bool {
+    // Compare as little-endian numbers, starting from the most significant byte.
+    element.iter().rev().lt(MODULUS.iter().rev())
+}
+
+fn encode_halves(value: &[u8; 32]) -> [FieldElementBytes; 2] {
+    let (low, high) = value.split_at(16);
+    [
+        encode_u128(u128::from_le_bytes(low.try_into().unwrap())),
+        encode_u128(u128::from_le_bytes(high.try_into().unwrap())),
+    ]
+}
+
+fn encode_u128(value: u128) -> FieldElementBytes {
+    let mut bytes = [0u8; FIELD_ELEMENT_SIZE];
+    bytes[..16].copy_from_slice(&value.to_le_bytes());
+    bytes
+}
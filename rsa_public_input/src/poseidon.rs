@@ -0,0 +1,99 @@
+//! Poseidon permutation and sponge over the BN254 scalar field.
+//!
+//! The parameters (see [`constants`]) are the standard ones for width 3, so the permutation is compatible with other
+//! implementations (e.g. `circomlib`). Being `no_std`, it can be evaluated both on the host and in the contract. The
+//! constants are public, so that circuits can evaluate exactly the same permutation.
+
+mod constants;
+
+pub use constants::{MDS, ROUND_CONSTANTS};
+
+use crate::field::Scalar;
+
+/// Number of field elements in the permutation state.
+pub const WIDTH: usize = 3;
+
+/// Number of elements absorbed (or squeezed) per permutation. The remaining element is the capacity.
+pub const RATE: usize = WIDTH - 1;
+
+/// Number of full rounds (half of them before and half after the partial ones).
+pub const FULL_ROUNDS: usize = 8;
+/// Number of partial rounds (with the S-box applied to the first element only).
+pub const PARTIAL_ROUNDS: usize = 57;
+/// Total number of rounds.
+pub const ROUNDS: usize = FULL_ROUNDS + PARTIAL_ROUNDS;
+
+/// Apply the Poseidon permutation to `state`.
+pub fn permute(state: &mut [Scalar; WIDTH]) {
+    for (round, constants) in ROUND_CONSTANTS.iter().enumerate() {
+        for (element, constant) in state.iter_mut().zip(constants) {
+            *element = *element + *constant;
+        }
+
+        let partial_rounds = FULL_ROUNDS / 2..FULL_ROUNDS / 2 + PARTIAL_ROUNDS;
+        if !partial_rounds.contains(&round) {
+            for element in state.iter_mut() {
+                *element = element.pow5();
+            }
+        } else {
+            state[0] = state[0].pow5();
+        }
+
+        let mut mixed = [Scalar::ZERO; WIDTH];
+        for (output, row) in mixed.iter_mut().zip(MDS) {
+            for (coefficient, element) in row.iter().zip(state.iter()) {
+                *output = *output + *coefficient * *element;
+            }
+        }
+        *state = mixed;
+    }
+}
+
+/// Duplex sponge built on top of [`permute`]. The first state element is the capacity.
+///
+/// The sponge doesn't pad its input: absorbing `[x]` and `[x, 0]` leads to the same state. This is fine as long as the
+/// sequence of operations is fixed by the protocol (like in a Fiat-Shamir transcript).
+#[derive(Clone, Debug)]
+pub struct Sponge {
+    state: [Scalar; WIDTH],
+    absorbed: usize,
+}
+
+impl Sponge {
+    /// Create a sponge with the capacity element set to `domain`, so that different applications don't collide.
+    pub fn new(domain: Scalar) -> Self {
+        Self {
+            state: [domain, Scalar::ZERO, Scalar::ZERO],
+            absorbed: 0,
+        }
+    }
+
+    /// Absorb a single element.
+    pub fn absorb(&mut self, element: Scalar) {
+        if self.absorbed == RATE {
+            permute(&mut self.state);
+            self.absorbed = 0;
+        }
+        self.state[1 + self.absorbed] = self.state[1 + self.absorbed] + element;
+        self.absorbed += 1;
+    }
+
+    /// Squeeze a single element. Every call permutes the state, so consecutive squeezes return independent values.
+    pub fn squeeze(&mut self) -> Scalar {
+        permute(&mut self.state);
+        self.absorbed = 0;
+        self.state[1]
+    }
+}
+
+/// Hash a fixed-length sequence of `elements`.
+///
+/// The length is encoded in the capacity element (as `length * 2^64`, following the convention of `halo2_gadgets`), so
+/// sequences of different lengths never collide despite the lack of padding.
+pub fn hash(elements: &[Scalar]) -> Scalar {
+    let mut sponge = Sponge::new(Scalar::from_u128((elements.len() as u128) << 64));
+    for element in elements {
+        sponge.absorb(*element);
+    }
+    sponge.squeeze()
+}
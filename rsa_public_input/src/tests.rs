@@ -0,0 +1,195 @@
+use proptest::prelude::*;
+
+use crate::{
+    encode, encode_bytes, encode_context, encode_hashed, encode_hashed_bytes, encode_preimage,
+    encode_preimage_bytes, encode_private_key, encode_private_key_bytes, encode_well_formedness,
+    encode_well_formedness_bytes, field::Scalar, hash_context, hash_preimage, is_canonical,
+    Account, Domain, FieldElementBytes, MAX_FACTOR_BITS, MIN_FACTOR_BITS, MODULUS,
+};
+
+mod account;
+mod field;
+mod poseidon;
+
+/// The layout that the contract used to produce by hand, before switching to this crate, extended with the domain.
+fn reference_encoding(n: u128, account: &Account, domain: &Domain) -> Vec<u8> {
+    let mut input = Vec::new();
+    input.extend(n.to_le_bytes());
+    input.extend([0u8; 16]);
+    for value in [
+        account.as_bytes(),
+        domain.contract.as_bytes(),
+        &domain.chain_id,
+    ] {
+        input.extend(u128::from_le_bytes(value[..16].try_into().unwrap()).to_le_bytes());
+        input.extend([0u8; 16]);
+        input.extend(u128::from_le_bytes(value[16..].try_into().unwrap()).to_le_bytes());
+        input.extend([0u8; 16]);
+    }
+    input
+}
+
+fn accounts() -> impl Strategy<Value = Account> {
+    any::<[u8; 32]>().prop_map(Account::new)
+}
+
+/// Copy of the 32-byte `account` with a single bit flipped.
+fn flip_bit(account: &Account) -> Account {
+    let mut bytes: [u8; 32] = account.as_bytes().try_into().unwrap();
+    bytes[31] ^= 1;
+    Account::new(bytes)
+}
+
+fn domains() -> impl Strategy<Value = Domain> {
+    any::<([u8; 32], [u8; 32])>().prop_map(|(contract, chain_id)| Domain {
+        contract: Account::new(contract),
+        chain_id,
+    })
+}
+
+proptest! {
+    #[test]
+    fn encoding_matches_reference(n: u128, account in accounts(), domain in domains()) {
+        prop_assert_eq!(
+            encode_bytes(n, &account, &domain).to_vec(),
+            reference_encoding(n, &account, &domain)
+        );
+    }
+
+    #[test]
+    fn elements_are_concatenated(n: u128, account in accounts(), domain in domains()) {
+        prop_assert_eq!(
+            encode(n, &account, &domain).concat(),
+            encode_bytes(n, &account, &domain).to_vec()
+        );
+    }
+
+    #[test]
+    fn elements_are_canonical(n: u128, account in accounts(), domain in domains()) {
+        for element in encode(n, &account, &domain) {
+            prop_assert!(is_canonical(&element));
+        }
+    }
+
+    #[test]
+    fn domain_is_encoded(n: u128, account in accounts(), domain in domains()) {
+        let mut other_contract = domain;
+        other_contract.contract = flip_bit(&domain.contract);
+        let mut other_chain = domain;
+        other_chain.chain_id[0] ^= 1;
+
+        let encoding = encode(n, &account, &domain);
+        prop_assert_ne!(encoding, encode(n, &account, &other_contract));
+        prop_assert_ne!(encoding, encode(n, &account, &other_chain));
+    }
+
+    #[test]
+    fn well_formedness_elements_are_concatenated(n: u128, factor_bits in MIN_FACTOR_BITS..=MAX_FACTOR_BITS) {
+        prop_assert_eq!(
+            encode_well_formedness(n, factor_bits).unwrap().concat(),
+            encode_well_formedness_bytes(n, factor_bits).unwrap().to_vec()
+        );
+    }
+
+    #[test]
+    fn hashed_elements_are_concatenated(n: u128, account in accounts(), domain in domains()) {
+        prop_assert_eq!(
+            encode_hashed(n, &account, &domain).concat(),
+            encode_hashed_bytes(n, &account, &domain).to_vec()
+        );
+    }
+
+    #[test]
+    fn context_is_hashed(n: u128, account in accounts(), domain in domains()) {
+        let [encoded_n, hash] = encode_hashed(n, &account, &domain);
+        prop_assert_eq!(encoded_n, encode(n, &account, &domain)[0]);
+        prop_assert!(is_canonical(&hash));
+        prop_assert_eq!(encode_context(&account, &domain).to_vec(), encode(n, &account, &domain)[1..].to_vec());
+
+        prop_assert_ne!(hash, hash_context(&flip_bit(&account), &domain));
+    }
+
+    #[test]
+    fn private_key_elements_are_concatenated(n: u128, e: u64, account in accounts(), domain in domains()) {
+        prop_assert_eq!(
+            encode_private_key(n, e, &account, &domain).concat(),
+            encode_private_key_bytes(n, e, &account, &domain).to_vec()
+        );
+    }
+
+    #[test]
+    fn private_key_encodes_exponent_and_context(n: u128, e: u64, account in accounts(), domain in domains()) {
+        let elements = encode_private_key(n, e, &account, &domain);
+        let raw = encode(n, &account, &domain);
+
+        prop_assert_eq!(elements[0], raw[0]);
+        prop_assert_eq!(u128::from_le_bytes(elements[1][..16].try_into().unwrap()), e as u128);
+        prop_assert_eq!(elements[2..].to_vec(), raw[1..].to_vec());
+        for element in elements {
+            prop_assert!(is_canonical(&element));
+        }
+    }
+
+    #[test]
+    fn preimage_elements_are_concatenated(preimage: u128, account in accounts(), domain in domains()) {
+        let hash = hash_preimage(&encode(preimage, &account, &domain)[0]).unwrap();
+        prop_assert_eq!(
+            encode_preimage(&hash, &account, &domain).unwrap().concat(),
+            encode_preimage_bytes(&hash, &account, &domain).unwrap().to_vec()
+        );
+    }
+
+    #[test]
+    fn preimage_encodes_hash_and_context(preimage: u128, account in accounts(), domain in domains()) {
+        let raw = encode(preimage, &account, &domain);
+        let hash = hash_preimage(&raw[0]).unwrap();
+        let elements = encode_preimage(&hash, &account, &domain).unwrap();
+
+        prop_assert_eq!(elements[0], hash);
+        prop_assert!(is_canonical(&hash));
+        prop_assert_eq!(elements[1..].to_vec(), raw[1..].to_vec());
+    }
+
+    #[test]
+    fn elements_above_modulus_are_not_canonical(mut element: FieldElementBytes) {
+        element[31] |= 0x40;
+        prop_assert!(!is_canonical(&element));
+    }
+}
+
+#[test]
+fn modulus_is_not_canonical() {
+    let mut below_modulus = MODULUS;
+    below_modulus[0] -= 1;
+
+    assert!(is_canonical(&below_modulus));
+    assert!(!is_canonical(&MODULUS));
+    assert!(!is_canonical(&[0xff; 32]));
+    assert!(is_canonical(&[0; 32]));
+}
+
+#[test]
+fn non_canonical_preimage_or_hash_is_rejected() {
+    let (account, domain) = (Account::default(), Domain::default());
+
+    assert!(hash_preimage(&MODULUS).is_none());
+    assert!(encode_preimage(&MODULUS, &account, &domain).is_none());
+    assert!(encode_preimage_bytes(&[0xff; 32], &account, &domain).is_none());
+    assert_eq!(
+        hash_preimage(&[0; 32]),
+        Some(crate::poseidon::hash(&[Scalar::ZERO]).to_bytes())
+    );
+}
+
+#[test]
+fn well_formedness_encodes_lower_bound() {
+    let [n, lower_bound] = encode_well_formedness(101 * 103, 7).unwrap();
+
+    assert_eq!(u128::from_le_bytes(n[..16].try_into().unwrap()), 101 * 103);
+    assert_eq!(
+        u128::from_le_bytes(lower_bound[..16].try_into().unwrap()),
+        64
+    );
+    assert!(encode_well_formedness(101 * 103, MIN_FACTOR_BITS - 1).is_none());
+    assert!(encode_well_formedness(101 * 103, MAX_FACTOR_BITS + 1).is_none());
+}
@@ -0,0 +1,68 @@
+use proptest::{collection::vec, prelude::*};
+
+use crate::{is_canonical, Account, H160_LENGTH, MAX_ACCOUNT_LENGTH};
+
+proptest! {
+    #[test]
+    fn bytes_roundtrip(identifier in vec(any::<u8>(), 0..=MAX_ACCOUNT_LENGTH)) {
+        let account = Account::from_slice(&identifier).unwrap();
+        prop_assert_eq!(account.as_bytes(), identifier.as_slice());
+    }
+
+    #[test]
+    fn formats_are_consistent(account: [u8; 32], address: [u8; 20]) {
+        prop_assert_eq!(Account::from(account), Account::from_slice(&account).unwrap());
+        prop_assert_eq!(Account::from(address), Account::from_slice(&address).unwrap());
+        prop_assert_eq!(Account::from_h160(address).as_bytes().to_vec(), address.to_vec());
+    }
+
+    #[test]
+    fn encoding_is_canonical(identifier in vec(any::<u8>(), 0..=MAX_ACCOUNT_LENGTH)) {
+        for element in Account::from_slice(&identifier).unwrap().encode() {
+            prop_assert!(is_canonical(&element));
+        }
+    }
+
+    #[test]
+    fn encoding_is_injective(
+        first in vec(any::<u8>(), 0..=MAX_ACCOUNT_LENGTH),
+        second in vec(any::<u8>(), 0..=MAX_ACCOUNT_LENGTH),
+    ) {
+        let (first_account, second_account) =
+            (Account::from_slice(&first).unwrap(), Account::from_slice(&second).unwrap());
+        prop_assert_eq!(first == second, first_account.encode() == second_account.encode());
+    }
+
+    #[test]
+    fn padded_h160_is_not_confused_with_account(address: [u8; 20]) {
+        let mut padded = [0u8; 32];
+        padded[..H160_LENGTH].copy_from_slice(&address);
+
+        prop_assert_ne!(Account::from_h160(address).encode(), Account::new(padded).encode());
+        // The lower halves are the same, only the length tells them apart.
+        prop_assert_eq!(Account::from_h160(address).encode()[0], Account::new(padded).encode()[0]);
+    }
+}
+
+#[test]
+fn long_identifiers_are_rejected() {
+    assert!(Account::from_slice(&[0; MAX_ACCOUNT_LENGTH + 1]).is_none());
+}
+
+#[test]
+fn empty_identifier_is_not_zero_account() {
+    let empty = Account::from_slice(&[]).unwrap();
+    assert_ne!(empty.encode(), Account::default().encode());
+    assert_ne!(empty.encode(), Account::from_h160([0; 20]).encode());
+}
+
+#[test]
+fn account_encoding_is_unchanged() {
+    // 32-byte accounts are split into halves exactly like before other formats were supported.
+    let account = Account::new(core::array::from_fn(|i| i as u8));
+    let [low, high] = account.encode();
+
+    assert_eq!(&low[..16], &account.as_bytes()[..16]);
+    assert_eq!(&high[..16], &account.as_bytes()[16..]);
+    assert!(low[16..].iter().chain(&high[16..]).all(|byte| *byte == 0));
+}
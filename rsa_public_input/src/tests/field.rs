@@ -0,0 +1,48 @@
+use proptest::prelude::*;
+
+use crate::{field::Scalar, FieldElementBytes, MODULUS};
+
+fn bytes(value: u128) -> FieldElementBytes {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&value.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn modulus_is_rejected() {
+    assert_eq!(Scalar::from_bytes(&MODULUS), None);
+    assert_eq!(Scalar::from_bytes(&[0xff; 32]), None);
+
+    let mut max = MODULUS;
+    max[0] -= 1;
+    assert_eq!(Scalar::from_bytes(&max).map(Scalar::to_bytes), Some(max));
+}
+
+#[test]
+fn arithmetic_wraps_around_modulus() {
+    let mut minus_one = MODULUS;
+    minus_one[0] -= 1;
+    let minus_one = Scalar::from_bytes(&minus_one).unwrap();
+
+    assert_eq!(minus_one + Scalar::from_u128(1), Scalar::ZERO);
+    assert_eq!(minus_one * minus_one, Scalar::from_u128(1));
+    assert_eq!(minus_one.pow5(), minus_one);
+}
+
+proptest! {
+    #[test]
+    fn bytes_roundtrip(value: [u8; 32]) {
+        let mut value = value;
+        value[31] &= 0x1f;
+        prop_assert_eq!(Scalar::from_bytes(&value).unwrap().to_bytes(), value);
+    }
+
+    #[test]
+    fn small_arithmetic_matches_integers(a: u64, b: u64) {
+        let (x, y) = (Scalar::from_u128(a as u128), Scalar::from_u128(b as u128));
+
+        prop_assert_eq!(x.to_bytes(), bytes(a as u128));
+        prop_assert_eq!((x + y).to_bytes(), bytes(a as u128 + b as u128));
+        prop_assert_eq!((x * y).to_bytes(), bytes(a as u128 * b as u128));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{
+    field::Scalar,
+    poseidon::{hash, permute, Sponge},
+};
+
+fn scalar(hex: &str) -> Scalar {
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        let position = hex.len() - 2 * (i + 1);
+        *byte = u8::from_str_radix(&hex[position..position + 2], 16).unwrap();
+    }
+    Scalar::from_bytes(&bytes).unwrap()
+}
+
+/// Test vector of the reference implementation (`poseidonperm_x5_254_3`).
+#[test]
+fn permutation_matches_reference() {
+    let mut state = [0, 1, 2].map(Scalar::from_u128);
+    permute(&mut state);
+
+    assert_eq!(
+        state,
+        [
+            scalar("115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"),
+            scalar("0fca49b798923ab0239de1c9e7a4a9a2210312b6a2f616d18b5a87f9b628ae29"),
+            scalar("0e7ae82e40091e63cbd4f16a6d16310b3729d4b6e138fcf54110e2867045a30c"),
+        ]
+    );
+}
+
+#[test]
+fn sponge_depends_on_domain_and_input() {
+    let squeeze = |domain: u128, input: &[u128]| {
+        let mut sponge = Sponge::new(Scalar::from_u128(domain));
+        input
+            .iter()
+            .for_each(|x| sponge.absorb(Scalar::from_u128(*x)));
+        sponge.squeeze()
+    };
+
+    assert_eq!(squeeze(0, &[1, 2, 3]), squeeze(0, &[1, 2, 3]));
+    assert_ne!(squeeze(0, &[1, 2, 3]), squeeze(1, &[1, 2, 3]));
+    assert_ne!(squeeze(0, &[1, 2, 3]), squeeze(0, &[1, 2, 4]));
+    assert_ne!(squeeze(0, &[1, 2, 3]), squeeze(0, &[2, 1, 3]));
+}
+
+#[test]
+fn consecutive_squeezes_differ() {
+    let mut sponge = Sponge::new(Scalar::ZERO);
+    sponge.absorb(Scalar::from_u128(1));
+
+    let first = sponge.squeeze();
+    assert_ne!(first, sponge.squeeze());
+}
+
+#[test]
+fn hash_depends_on_length() {
+    let one = Scalar::from_u128(1);
+
+    assert_eq!(hash(&[one, one]), hash(&[one, one]));
+    assert_ne!(hash(&[one]), hash(&[one, Scalar::ZERO]));
+    assert_ne!(hash(&[]), hash(&[Scalar::ZERO]));
+}